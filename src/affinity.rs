@@ -0,0 +1,116 @@
+#[cfg(target_os = "linux")]
+use std::fs;
+use std::io;
+
+// ids of the online CPUs, from /sys/devices/system/cpu/online ("0-3,6,8-11"); they match the cpuN names in the Core
+// Information panel but needn't be contiguous. Ids a cpu_set_t can't hold are left out.
+#[cfg(target_os = "linux")]
+pub fn online_cpus(cpu_count: usize) -> Vec<usize> {
+    let listed = fs::read_to_string("/sys/devices/system/cpu/online").ok().and_then(|text| parse_cpu_list(text.trim()));
+    let mut cpus = listed.unwrap_or_else(|| (0..cpu_count).collect());
+    cpus.retain(|cpu| *cpu < libc::CPU_SETSIZE as usize);
+    cpus
+}
+
+#[cfg(target_os = "linux")]
+fn parse_cpu_list(text: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in text.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<usize>().ok()?..=last.parse::<usize>().ok()?),
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+// whether the process may run on each of `cpus`
+#[cfg(target_os = "linux")]
+pub fn get_affinity(pid: u32, cpus: &[usize]) -> io::Result<Vec<bool>> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let result = unsafe {
+        libc::sched_getaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &mut set)
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(cpus.iter().map(|cpu| unsafe { libc::CPU_ISSET(*cpu, &set) }).collect())
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_affinity(pid: u32, cpus: &[usize], allowed: &[bool]) -> io::Result<()> {
+    if !allowed.iter().any(|a| *a) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "at least one CPU must be allowed"));
+    }
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for (cpu, _) in cpus.iter().zip(allowed).filter(|(_, a)| **a) {
+        unsafe { libc::CPU_SET(*cpu, &mut set) };
+    }
+    let result = unsafe {
+        libc::sched_setaffinity(pid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), &set)
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn online_cpus(cpu_count: usize) -> Vec<usize> {
+    (0..cpu_count).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_affinity(_pid: u32, _cpus: &[usize]) -> io::Result<Vec<bool>> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "CPU affinity is only available on Linux"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_affinity(_pid: u32, _cpus: &[usize], _allowed: &[bool]) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "CPU affinity is only available on Linux"))
+}
+
+// state of the affinity popup for one process
+pub struct AffinityEdit {
+    pub pid: u32,
+    pub name: String,
+    // CPU ids, one per grid cell
+    pub cpus: Vec<usize>,
+    pub allowed: Vec<bool>,
+    pub original: Vec<bool>,
+    pub cursor: usize,
+    // cores per row of the grid, set when it is drawn
+    pub columns: usize,
+    // first grid row shown, for hosts with more cores than fit
+    pub scroll: usize,
+}
+
+impl AffinityEdit {
+    pub fn new(pid: u32, name: String, cpus: Vec<usize>, allowed: Vec<bool>) -> Self {
+        Self { pid, name, cpus, original: allowed.clone(), allowed, cursor: 0, columns: 8, scroll: 0 }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(a) = self.allowed.get_mut(self.cursor) {
+            *a = !*a;
+        }
+    }
+
+    // select every core, or none if they already all are
+    pub fn toggle_all(&mut self) {
+        let all = self.allowed.iter().all(|a| *a);
+        self.allowed.iter_mut().for_each(|a| *a = !all);
+    }
+
+    // move the cursor by (columns, rows) within the grid
+    pub fn move_cursor(&mut self, dx: isize, dy: isize) {
+        let target = self.cursor as isize + dx + dy * self.columns.max(1) as isize;
+        if target >= 0 && (target as usize) < self.allowed.len() {
+            self.cursor = target as usize;
+        }
+    }
+
+    pub fn changed(&self) -> bool {
+        self.allowed != self.original
+    }
+}
@@ -0,0 +1,150 @@
+// collapsing the process table into one row per name, user or parent
+use std::collections::{HashMap, HashSet};
+use sysinfo::{Pid, Process, System, Users};
+use crate::events::ExitedProcess;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Off,
+    Name,
+    User,
+    Parent,
+}
+
+impl Aggregate {
+    pub fn next(self) -> Self {
+        match self {
+            Aggregate::Off => Aggregate::Name,
+            Aggregate::Name => Aggregate::User,
+            Aggregate::User => Aggregate::Parent,
+            Aggregate::Parent => Aggregate::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Aggregate::Off => "off",
+            Aggregate::Name => "name",
+            Aggregate::User => "user",
+            Aggregate::Parent => "parent",
+        }
+    }
+}
+
+pub struct ProcessGroup<'a> {
+    pub key: String,
+    pub members: Vec<&'a Process>,
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub expanded: bool,
+}
+
+// one row of the process table
+pub enum TableEntry<'a> {
+    Group(ProcessGroup<'a>),
+    // a process listed under its expanded group
+    Member(&'a Process),
+    Process(&'a Process),
+    // a process that exited a moment ago, greyed out where it was
+    Exited(&'a ExitedProcess),
+}
+
+impl TableEntry<'_> {
+    pub fn pid(&self) -> Option<Pid> {
+        match self {
+            TableEntry::Group(_) | TableEntry::Exited(_) => None,
+            TableEntry::Member(p) | TableEntry::Process(p) => Some(p.pid()),
+        }
+    }
+}
+
+// what a process is grouped under
+pub fn group_key(process: &Process, mode: Aggregate, s: &System, users: &Users) -> String {
+    match mode {
+        Aggregate::Off => process.pid().to_string(),
+        Aggregate::Name => process.name().to_string_lossy().to_string(),
+        Aggregate::User => process
+            .user_id()
+            .map(|uid| match users.get_user_by_id(uid) {
+                Some(user) => user.name().to_string(),
+                None => uid.to_string(),
+            })
+            .unwrap_or(String::from("?")),
+        Aggregate::Parent => match process.parent() {
+            Some(ppid) => match s.process(ppid) {
+                Some(parent) => format!("{} ({})", parent.name().to_string_lossy(), ppid),
+                None => ppid.to_string(),
+            },
+            None => String::from("none"),
+        },
+    }
+}
+
+// turns the sorted, filtered process list into table rows; groups of one stay plain processes
+pub fn entries<'a>(
+    process_list: &[&'a Process],
+    mode: Aggregate,
+    s: &System,
+    users: &Users,
+    expanded: &HashSet<String>,
+    sort_col: u8,
+    reverse: bool,
+) -> Vec<TableEntry<'a>> {
+    if mode == Aggregate::Off {
+        return process_list.iter().map(|p| TableEntry::Process(p)).collect();
+    }
+
+    // members keep the order of the process list, and groups start in order of first appearance
+    let mut groups: Vec<ProcessGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for process in process_list {
+        let key = group_key(process, mode, s, users);
+        let i = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(ProcessGroup { expanded: expanded.contains(&key), key, members: Vec::new(), cpu_usage: 0.0, memory: 0 });
+            groups.len() - 1
+        });
+        let group = &mut groups[i];
+        group.members.push(process);
+        group.cpu_usage += process.cpu_usage();
+        group.memory += process.memory();
+    }
+
+    // order groups by their totals where the sort column has one
+    let ordered = match sort_col {
+        0 => {
+            // newest first, like the pid column
+            groups.sort_by_key(|g| std::cmp::Reverse(g.members.iter().map(|p| p.pid()).min()));
+            true
+        }
+        1 => {
+            groups.sort_by_key(|g| g.key.to_lowercase());
+            true
+        }
+        2 => {
+            groups.sort_by_key(|g| std::cmp::Reverse(g.memory));
+            true
+        }
+        3 => {
+            groups.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage));
+            true
+        }
+        _ => false,
+    };
+    if ordered && reverse {
+        groups.reverse();
+    }
+
+    let mut entries = Vec::new();
+    for group in groups {
+        if group.members.len() == 1 {
+            entries.push(TableEntry::Process(group.members[0]));
+        } else if group.expanded {
+            let members = group.members.clone();
+            entries.push(TableEntry::Group(group));
+            entries.extend(members.into_iter().map(TableEntry::Member));
+        } else {
+            entries.push(TableEntry::Group(group));
+        }
+    }
+    entries
+}
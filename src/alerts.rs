@@ -0,0 +1,372 @@
+// threshold alerts from `alert.<name>=` lines in ~/.xtop/xtoprc, checked on every refresh:
+//   alert.hot=core > 95 for 30s bell
+//   alert.lowmem=mem_avail < 1G run=notify-send "xtop" "$XTOP_ALERT_MESSAGE"
+//   alert.db=exit postgres
+// `run=` comes last and takes the rest of the line
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::path::Path;
+use sysinfo::{Process, System};
+
+// how long a panel keeps flashing after a one-off alert such as a process exiting
+const FLASH_TIME: Duration = Duration::from_secs(10);
+
+// the panel whose border flashes while an alert is on
+#[derive(Clone, Copy, PartialEq)]
+pub enum Panel {
+    Cpu,
+    Memory,
+    Processes,
+}
+
+#[derive(Clone, Copy)]
+enum Metric {
+    // total usage and the busiest core, in %
+    Cpu,
+    Core,
+    // 1-minute load average
+    Load,
+    // bytes
+    MemAvail,
+    MemUsed,
+    Swap,
+}
+
+impl Metric {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(Metric::Cpu),
+            "core" => Some(Metric::Core),
+            "load" => Some(Metric::Load),
+            "mem_avail" => Some(Metric::MemAvail),
+            "mem_used" => Some(Metric::MemUsed),
+            "swap" => Some(Metric::Swap),
+            _ => None,
+        }
+    }
+
+    fn value(self, s: &System) -> f64 {
+        match self {
+            Metric::Cpu => s.global_cpu_usage() as f64,
+            Metric::Core => s.cpus().iter().map(|c| c.cpu_usage() as f64).fold(0.0, f64::max),
+            Metric::Load => System::load_average().one,
+            Metric::MemAvail => s.available_memory() as f64,
+            Metric::MemUsed => s.used_memory() as f64,
+            Metric::Swap => s.used_swap() as f64,
+        }
+    }
+
+    fn format(self, value: f64) -> String {
+        match self {
+            Metric::Cpu | Metric::Core => format!("{:.0}%", value),
+            Metric::Load => format!("{:.2}", value),
+            Metric::MemAvail | Metric::MemUsed | Metric::Swap => format!("{:.1} GB", value / 1_073_741_824.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Above,
+    AtLeast,
+    Below,
+    AtMost,
+}
+
+#[derive(Clone, Copy)]
+enum Limit {
+    Value(f64),
+    // the number of cores, for `load > cores`
+    Cores,
+}
+
+enum Condition {
+    Threshold { metric: Metric, op: Op, limit: Limit, text: String },
+    // every process with this name has gone
+    Exit(String),
+}
+
+pub struct Alert {
+    pub name: String,
+    condition: Condition,
+    // how long a threshold has to hold before the alert goes off
+    duration: Duration,
+    bell: bool,
+    command: Option<String>,
+    // when the threshold was first crossed, while it still is
+    since: Option<Instant>,
+    firing: bool,
+    // for exit alerts: whether the process was running at the last check
+    running: bool,
+    flash_until: Option<Instant>,
+}
+
+impl Alert {
+    pub fn parse(name: &str, value: &str) -> Result<Self, String> {
+        let (rule, command) = match value.find("run=") {
+            Some(i) => (&value[..i], Some(value[i + "run=".len()..].trim().to_string()).filter(|c| !c.is_empty())),
+            None => (value, None),
+        };
+        let mut words: Vec<&str> = rule.split_whitespace().collect();
+        let bell = words.contains(&"bell");
+        words.retain(|w| *w != "bell");
+        let mut duration = Duration::ZERO;
+        if let Some(i) = words.iter().position(|w| *w == "for") {
+            let text = words.get(i + 1).ok_or("missing time after 'for'")?;
+            duration = parse_duration(text)?;
+            if words.len() > i + 2 {
+                return Err(format!("unexpected '{}'", words[i + 2]));
+            }
+            words.truncate(i);
+        }
+
+        let condition = match words.as_slice() {
+            ["exit", process] => Condition::Exit(process.to_string()),
+            [metric, op, limit] => {
+                let metric = Metric::from_name(metric).ok_or(format!("unknown metric '{}'", metric))?;
+                let op = match *op {
+                    ">" => Op::Above,
+                    ">=" => Op::AtLeast,
+                    "<" => Op::Below,
+                    "<=" => Op::AtMost,
+                    _ => return Err(format!("unknown operator '{}'", op)),
+                };
+                let parsed = match (limit.to_lowercase().as_str(), metric) {
+                    ("cores", _) => Limit::Cores,
+                    (_, Metric::MemAvail | Metric::MemUsed | Metric::Swap) => Limit::Value(parse_size(limit)?),
+                    (_, _) => Limit::Value(
+                        limit.trim_end_matches('%').parse().map_err(|_| format!("'{}' is not a number", limit))?,
+                    ),
+                };
+                Condition::Threshold { metric, op, limit: parsed, text: words.join(" ") }
+            }
+            _ => return Err(String::from("expected '<metric> <op> <value>' or 'exit <name>'")),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            condition,
+            duration,
+            bell,
+            command,
+            since: None,
+            firing: false,
+            running: false,
+            flash_until: None,
+        })
+    }
+
+    pub fn panel(&self) -> Panel {
+        match self.condition {
+            Condition::Threshold { metric: Metric::Cpu | Metric::Core | Metric::Load, .. } => Panel::Cpu,
+            Condition::Threshold { .. } => Panel::Memory,
+            Condition::Exit(_) => Panel::Processes,
+        }
+    }
+
+    // a threshold that still holds, or a one-off alert that went off a moment ago
+    pub fn is_on(&self, now: Instant) -> bool {
+        self.firing || self.flash_until.is_some_and(|t| now < t)
+    }
+
+    // the message to show when the alert goes off, once per crossing
+    pub fn check(&mut self, s: &System, now: Instant) -> Option<String> {
+        match &self.condition {
+            Condition::Threshold { metric, op, limit, text } => {
+                let value = metric.value(s);
+                let limit = match limit {
+                    Limit::Value(v) => *v,
+                    Limit::Cores => s.cpus().len() as f64,
+                };
+                let crossed = match op {
+                    Op::Above => value > limit,
+                    Op::AtLeast => value >= limit,
+                    Op::Below => value < limit,
+                    Op::AtMost => value <= limit,
+                };
+                if !crossed {
+                    self.since = None;
+                    self.firing = false;
+                    return None;
+                }
+                let since = *self.since.get_or_insert(now);
+                if self.firing || now.duration_since(since) < self.duration {
+                    return None;
+                }
+                self.firing = true;
+                Some(format!("Alert {}: {} ({})", self.name, text, metric.format(value)))
+            }
+            Condition::Exit(process) => {
+                let running = s.processes().values().any(|p| is_named(p, process));
+                let exited = self.running && !running;
+                self.running = running;
+                if !exited {
+                    return None;
+                }
+                self.flash_until = Some(now + FLASH_TIME);
+                Some(format!("Alert {}: {} exited", self.name, process))
+            }
+        }
+    }
+
+    // the bell and the command hook; the command runs in the background with the alert in its environment
+    pub fn notify(&self, message: &str) {
+        if self.bell {
+            use std::io::Write;
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07");
+            let _ = stdout.flush();
+        }
+        if let Some(command) = &self.command {
+            let mut child = Command::new("sh");
+            child
+                .arg("-c")
+                .arg(command)
+                .env("XTOP_ALERT", &self.name)
+                .env("XTOP_ALERT_MESSAGE", message)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            // waited on from its own thread so it doesn't linger as a zombie
+            thread::spawn(move || {
+                let _ = child.status();
+            });
+        }
+    }
+}
+
+// Linux cuts process names to 15 chars, so longer names are matched against the executable and argv[0] too
+fn is_named(process: &Process, name: &str) -> bool {
+    let basename = |path: &Path| path.file_name().is_some_and(|n| n == name);
+    process.name() == name
+        || process.exe().is_some_and(basename)
+        || process.cmd().first().is_some_and(|arg0| basename(Path::new(arg0)))
+}
+
+// `30s`, `5m`, `1h`, or plain seconds
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("'{}' is not a time", text))?;
+    let unit = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(format!("'{}' is not a time", text)),
+    };
+    let seconds = number.checked_mul(unit).ok_or(format!("'{}' is too long", text))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+// sizes take K, M, G or T; a bare number is in GB, like the memory panel
+fn parse_size(text: &str) -> Result<f64, String> {
+    let lower = text.to_lowercase();
+    let split = lower.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("'{}' is not a size", text))?;
+    let unit = match unit.trim_end_matches('b') {
+        "k" => 1024.0,
+        "m" => 1_048_576.0,
+        "g" | "" => 1_073_741_824.0,
+        "t" => 1_099_511_627_776.0,
+        _ => return Err(format!("unknown unit in '{}'", text)),
+    };
+    Ok(number * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn threshold(value: &str) -> (Metric, Op, Limit, String) {
+        match Alert::parse("test", value).map(|a| a.condition) {
+            Ok(Condition::Threshold { metric, op, limit, text }) => (metric, op, limit, text),
+            Ok(Condition::Exit(name)) => panic!("'{}' parsed as an exit alert for {}", value, name),
+            Err(e) => panic!("'{}' didn't parse: {}", value, e),
+        }
+    }
+
+    fn error(value: &str) -> String {
+        Alert::parse("test", value).err().unwrap_or_else(|| panic!("'{}' parsed", value))
+    }
+
+    #[test]
+    fn parses_thresholds() {
+        let (metric, op, limit, text) = threshold("core > 95");
+        assert!(matches!((metric, op, limit), (Metric::Core, Op::Above, Limit::Value(v)) if v == 95.0));
+        assert_eq!(text, "core > 95");
+        assert!(matches!(threshold("cpu >= 90%"), (Metric::Cpu, Op::AtLeast, Limit::Value(v), _) if v == 90.0));
+        assert!(matches!(threshold("load > cores"), (Metric::Load, Op::Above, Limit::Cores, _)));
+        assert!(matches!(threshold("load <= 2.5"), (Metric::Load, Op::AtMost, Limit::Value(v), _) if v == 2.5));
+        assert!(matches!(threshold("swap > 0"), (Metric::Swap, Op::Above, Limit::Value(v), _) if v == 0.0));
+    }
+
+    #[test]
+    fn memory_limits_are_sizes() {
+        let limit = |value| match threshold(value).2 {
+            Limit::Value(v) => v,
+            Limit::Cores => panic!("'{}' parsed as cores", value),
+        };
+        assert_eq!(limit("mem_avail < 1G"), 1_073_741_824.0);
+        assert_eq!(limit("mem_avail < 2"), 2.0 * 1_073_741_824.0);
+        assert_eq!(limit("mem_used >= 512M"), 512.0 * 1_048_576.0);
+        assert_eq!(limit("swap > 100kb"), 100.0 * 1024.0);
+        assert_eq!(limit("mem_used > 1.5T"), 1.5 * 1_099_511_627_776.0);
+    }
+
+    #[test]
+    fn parses_options() {
+        let alert = Alert::parse("hot", "core > 95 for 30s bell").unwrap();
+        assert_eq!(alert.name, "hot");
+        assert_eq!(alert.duration, Duration::from_secs(30));
+        assert!(alert.bell);
+        assert!(alert.command.is_none());
+        assert!(alert.panel() == Panel::Cpu);
+
+        let alert = Alert::parse("overload", "load > cores for 1m").unwrap();
+        assert_eq!(alert.duration, Duration::from_secs(60));
+        assert!(!alert.bell);
+
+        let alert = Alert::parse("db", r#"exit postgres bell run=notify-send xtop "$XTOP_ALERT_MESSAGE""#).unwrap();
+        assert!(matches!(&alert.condition, Condition::Exit(name) if name == "postgres"));
+        assert!(alert.bell);
+        assert_eq!(alert.command.as_deref(), Some(r#"notify-send xtop "$XTOP_ALERT_MESSAGE""#));
+        assert!(alert.panel() == Panel::Processes);
+
+        // an empty command is no command
+        assert!(Alert::parse("low", "mem_avail < 1G run=  ").unwrap().command.is_none());
+        assert!(Alert::parse("low", "mem_avail < 1G").unwrap().panel() == Panel::Memory);
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        assert_eq!(error(""), "expected '<metric> <op> <value>' or 'exit <name>'");
+        assert_eq!(error("exit"), "expected '<metric> <op> <value>' or 'exit <name>'");
+        assert_eq!(error("cpu > 5 10"), "expected '<metric> <op> <value>' or 'exit <name>'");
+        assert_eq!(error("temp > 5"), "unknown metric 'temp'");
+        assert_eq!(error("cpu = 5"), "unknown operator '='");
+        assert_eq!(error("cpu > hot"), "'hot' is not a number");
+        assert_eq!(error("mem_avail < 1Q"), "unknown unit in '1Q'");
+        assert_eq!(error("mem_avail < lots"), "'lots' is not a size");
+        assert_eq!(error("cpu > 5 for"), "missing time after 'for'");
+        assert_eq!(error("cpu > 5 for 10x"), "'10x' is not a time");
+        assert_eq!(error("cpu > 5 for 10s now"), "unexpected 'now'");
+    }
+
+    #[test]
+    fn parse_duration_units_and_overflow() {
+        assert_eq!(parse_duration("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7200)));
+        assert_eq!(parse_duration(""), Err(String::from("'' is not a time")));
+        assert_eq!(parse_duration("-5s"), Err(String::from("'-5s' is not a time")));
+        assert_eq!(parse_duration("1.5m"), Err(String::from("'1.5m' is not a time")));
+        assert_eq!(parse_duration("5d"), Err(String::from("'5d' is not a time")));
+        assert_eq!(parse_duration("99999999999999999999s"), Err(String::from("'99999999999999999999s' is not a time")));
+        assert_eq!(parse_duration("18446744073709551615s"), Ok(Duration::from_secs(u64::MAX)));
+        assert_eq!(parse_duration("18446744073709551615m"), Err(String::from("'18446744073709551615m' is too long")));
+        assert_eq!(parse_duration("6000000000000000000h"), Err(String::from("'6000000000000000000h' is too long")));
+        assert_eq!(error("cpu > 5 for 6000000000000000000h"), "'6000000000000000000h' is too long");
+    }
+}
@@ -1,29 +1,151 @@
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use ratatui::{backend::Backend, widgets::TableState, Terminal};
 use std::io;
 use std::time::{Duration, Instant};
 use std::sync::mpsc::{self, Receiver};
 use std::thread;
-use sysinfo::{Networks, ProcessRefreshKind, ProcessesToUpdate, System};
-use syntect::highlighting::ThemeSet;
-use ratatui::prelude::Color;
+use sysinfo::{Networks, Pid, ProcessRefreshKind, ProcessesToUpdate, System, Users};
+use syntect::highlighting::Theme;
+use crate::theme::ThemeLibrary;
+use crate::priority::{PriorityEdit, NICE_MAX, NICE_MIN};
+use crate::affinity::AffinityEdit;
+use crate::threads::ThreadView;
+use crate::connections::{ConnectionSort, ConnectionView};
+use crate::net::{ProcessTraffic, SocketOwners};
+use crate::cgroups::{self, Cgroup, GroupView};
+use std::collections::{HashMap, HashSet};
+use crate::aggregate::Aggregate;
+use crate::search::SearchMode;
+use crate::views::{SavedView, ViewPicker};
+use crate::history::{History, HistorySearch};
+use crate::editor::LineEditor;
+use crate::alerts::Alert;
+use crate::events::{EventLog, EventView};
+use crate::pins::Pins;
+use crate::bulk::{self, BulkMenu, Stage};
+use crate::config::Config;
+use crate::procfs;
 use crate::ui::ui;
 
+// which per-process disk I/O columns the table shows
+#[derive(Clone, Copy, PartialEq)]
+pub enum DiskColumns {
+    Off,
+    Rates,
+    Totals,
+}
+
+impl DiskColumns {
+    fn next(self) -> Self {
+        match self {
+            DiskColumns::Off => DiskColumns::Rates,
+            DiskColumns::Rates => DiskColumns::Totals,
+            DiskColumns::Totals => DiskColumns::Off,
+        }
+    }
+}
+
+// whether the process table shows each process's cgroup, and whether it groups by it
+#[derive(Clone, Copy, PartialEq)]
+pub enum CgroupColumn {
+    Off,
+    Shown,
+    Grouped,
+}
+
+impl CgroupColumn {
+    fn next(self) -> Self {
+        match self {
+            CgroupColumn::Off => CgroupColumn::Shown,
+            CgroupColumn::Shown => CgroupColumn::Grouped,
+            CgroupColumn::Grouped => CgroupColumn::Off,
+        }
+    }
+}
+
 pub struct App {
     pub(crate) s: System,
+    // the user list, re-read with the processes rather than on every draw
+    pub(crate) users: Users,
     pub(crate) networks: Networks,
     pub(crate) update_freq: u64,
     pub(crate) table_state: TableState,
-    pub(crate) filter_text: String,
-    pub(crate) cursor_position: usize,
+    pub(crate) search_input: LineEditor,
+    pub(crate) search_mode: SearchMode,
+    // match plain-text searches against the command line and path too
+    pub(crate) search_cmdline: bool,
     pub(crate) sort_col: u8,
     pub(crate) current_col: u8,
     pub(crate) reverse: bool,
     pub(crate) editing: bool,
     pub(crate) show_popup: bool,
+    // first line of the help popup shown
+    pub(crate) help_scroll: u16,
     pub(crate) process_info: u8,
-    theme_set: ThemeSet,
+    pub(crate) details_tab: usize,
+    pub(crate) details_scroll: u16,
+    // substring filter over the rows of the current details tab
+    pub(crate) details_filter: String,
+    pub(crate) details_filter_editing: bool,
+    pub(crate) selected_pid: Option<Pid>,
+    // the row selected_pid was on last frame; while the cursor stays there it follows the process
+    pub(crate) selected_row: Option<usize>,
+    // the process on each row last frame, to tell which one a key moved the cursor to
+    pub(crate) row_pids: Vec<Option<Pid>>,
+    // scroll the table to keep the followed process in view
+    pub(crate) follow: bool,
+    pub(crate) priority_edit: Option<PriorityEdit>,
+    pub(crate) affinity_edit: Option<AffinityEdit>,
+    pub(crate) show_last_cpu: bool,
+    // the CPU each process last ran on, read on the refresh tick while the column or a query shows it
+    pub(crate) last_cpus: HashMap<Pid, usize>,
+    pub(crate) thread_view: Option<ThreadView>,
+    pub(crate) connection_view: Option<ConnectionView>,
+    // row to select once the process table has been sorted
+    pub(crate) jump_to_pid: Option<Pid>,
+    pub(crate) disk_columns: DiskColumns,
+    // per-process network rates, sampled only while their columns are shown
+    pub(crate) net_traffic: Option<ProcessTraffic>,
+    // shared by the network columns and the connections view
+    socket_owners: SocketOwners,
+    pub(crate) cgroup_column: CgroupColumn,
+    // read only while something shows them
+    pub(crate) cgroups: HashMap<Pid, Cgroup>,
+    // label of the container or unit the process table is limited to
+    pub(crate) group_filter: Option<String>,
+    pub(crate) group_view: Option<GroupView>,
+    pub(crate) aggregate: Aggregate,
+    // keys of the aggregated rows opened up to show their processes
+    pub(crate) expanded_groups: HashSet<String>,
+    // set instead of selected_pid when the cursor is on an aggregated row
+    pub(crate) selected_group: Option<String>,
+    // seconds between the last two process refreshes, for per-second rates
+    pub(crate) refresh_secs: f64,
+    last_refresh: Instant,
+    pub(crate) theme_picker: bool,
+    pub(crate) theme_filter: String,
+    pub(crate) theme_picker_state: TableState,
+    theme_before_picker: String,
+    themes: ThemeLibrary,
     available_themes: Vec<String>,
+    color_overrides: Vec<(String, String)>,
+    // named views from the config, in file order; the first nine are on keys 1-9
+    pub(crate) views: Vec<SavedView>,
+    pub(crate) view_picker: Option<ViewPicker>,
+    pub(crate) history: History,
+    pub(crate) history_search: Option<HistorySearch>,
+    pub(crate) alerts: Vec<Alert>,
+    pub(crate) events: EventLog,
+    pub(crate) event_view: Option<EventView>,
+    pub(crate) pins: Pins,
+    // the name pattern being typed after `K`
+    pub(crate) pin_prompt: Option<LineEditor>,
+    // processes tagged for a bulk action, and every process the table lists right now, by pid and
+    // start time so a tag doesn't pass to a process that reuses the pid
+    pub(crate) tagged: HashSet<(Pid, u64)>,
+    pub(crate) listed: Vec<(Pid, u64)>,
+    pub(crate) bulk_menu: Option<BulkMenu>,
+    color_mode: crate::config::ColorMode,
     pub current_theme: String,
     pub ui_colors: crate::config::UiColors,
     pub theme_changed_time: Option<Instant>,
@@ -33,9 +155,15 @@ pub struct App {
     pub update_version: Option<String>,
 }
 
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl App {
 
-    fn show_notification(&mut self, msg: String) {
+    pub(crate) fn show_notification(&mut self, msg: String) {
         self.notification = Some(msg);
         self.notification_time = Some(Instant::now());
     }
@@ -44,16 +172,12 @@ impl App {
         let mut table_state = TableState::default();
         table_state.select(Some(0)); // Start with first row selected
 
-        let mut theme_set = syntect::highlighting::ThemeSet::load_defaults();
+        let theme_dir = crate::config::Config::get_theme_dir();
+        let themes = ThemeLibrary::load(theme_dir.as_deref());
+        let available_themes = themes.names();
 
-        if let Some(theme_dir) = crate::config::Config::get_theme_dir() {
-            let _ = theme_set.add_from_folder(&theme_dir);
-        }
-
-        let mut available_themes: Vec<String> = theme_set.themes.keys().cloned().collect();
-        available_themes.sort();
-
-        let saved_theme = crate::config::Config::load_config();
+        let settings = crate::config::Config::load_config();
+        let saved_theme = settings.theme.clone();
 
         let current_theme = if available_themes.contains(&saved_theme) {
             saved_theme // Use the user's saved preference
@@ -65,18 +189,20 @@ impl App {
             "No-Themes-Found".to_string()
         };
 
-        let ui_colors = if let Some(theme) = theme_set.themes.get(&current_theme) {
-            crate::config::UiColors::from_theme(theme)
-        } else {
-            // fallback if parsing fails
-            crate::config::UiColors {
-                bg: Color::Rgb(0,0,0), fg: Color::Rgb(255,255,255),
-                menu_bg: Color::Rgb(40,40,40), selected_bg: Color::Rgb(60,60,60),
-                accent: Color::Rgb(100,200,255),
-                title: Color::Rgb(200, 200, 100),
-                is_dark: true,
+        let color_mode = crate::config::ColorMode::from_setting(&settings.color_mode);
+        let fallback = Theme::default(); // fallback if parsing fails
+        let (theme, preset) = themes.get(&current_theme).unwrap_or((&fallback, &[]));
+        let overrides = [preset, settings.color_overrides.as_slice()].concat();
+        let (ui_colors, rejected_colors) = crate::config::UiColors::resolve(theme, &overrides, color_mode);
+
+        let mut alerts = Vec::new();
+        let mut invalid_alerts = Vec::new();
+        for (name, rule) in &settings.alerts {
+            match Alert::parse(name, rule) {
+                Ok(alert) => alerts.push(alert),
+                Err(e) => invalid_alerts.push(format!("{} ({})", name, e)),
             }
-        };
+        }
 
         let (update_tx, update_rx) = mpsc::channel();
         thread::spawn(move || {
@@ -97,21 +223,69 @@ impl App {
             }
         });
 
-        Self {
+        let mut app = Self {
             s: System::new_all(),
+            users: Users::new_with_refreshed_list(),
             networks: Networks::new_with_refreshed_list(),
             update_freq: 1000,
             table_state,
-            filter_text: String::new(),
-            cursor_position: 0,
+            search_input: LineEditor::default(),
+            search_mode: SearchMode::Substring,
+            search_cmdline: false,
             sort_col: 2,
             current_col: 2,
             reverse: false,
             editing: false,
             show_popup: false,
+            help_scroll: 0,
             process_info: 0,
-            theme_set,
+            details_tab: 0,
+            details_scroll: 0,
+            details_filter: String::new(),
+            details_filter_editing: false,
+            selected_pid: None,
+            selected_row: None,
+            row_pids: Vec::new(),
+            follow: false,
+            priority_edit: None,
+            affinity_edit: None,
+            show_last_cpu: false,
+            last_cpus: HashMap::new(),
+            thread_view: None,
+            connection_view: None,
+            jump_to_pid: None,
+            disk_columns: DiskColumns::Off,
+            net_traffic: None,
+            socket_owners: SocketOwners::default(),
+            cgroup_column: CgroupColumn::Off,
+            cgroups: HashMap::new(),
+            group_filter: None,
+            group_view: None,
+            aggregate: Aggregate::Off,
+            expanded_groups: HashSet::new(),
+            selected_group: None,
+            refresh_secs: 1.0,
+            last_refresh: Instant::now(),
+            theme_picker: false,
+            theme_filter: String::new(),
+            theme_picker_state: TableState::default(),
+            theme_before_picker: String::new(),
+            themes,
             available_themes,
+            color_overrides: settings.color_overrides,
+            views: settings.views.iter().map(|(name, value)| SavedView::parse(name, value)).collect(),
+            view_picker: None,
+            history: History::load(),
+            history_search: None,
+            alerts,
+            events: EventLog::default(),
+            event_view: None,
+            pins: Pins::parse(&settings.pins),
+            pin_prompt: None,
+            tagged: HashSet::new(),
+            listed: Vec::new(),
+            bulk_menu: None,
+            color_mode,
             current_theme,
             ui_colors,
             theme_changed_time: None,
@@ -119,6 +293,40 @@ impl App {
             notification_time: None,
             update_rx: Some(update_rx),
             update_version: None,
+        };
+
+        // startup problems share one notification, so none of them hides another
+        let mut problems = Vec::new();
+        let failed_themes = app.themes.failed_count();
+        if failed_themes > 0 {
+            problems.push(format!("{} theme file(s) failed to load, run xtop --check-themes", failed_themes));
+        }
+        if !rejected_colors.is_empty() {
+            problems.push(format!("Ignored invalid colors: {}", rejected_colors.join(", ")));
+        }
+        if !invalid_alerts.is_empty() {
+            problems.push(format!("Ignored invalid alerts: {}", invalid_alerts.join(", ")));
+        }
+        if !problems.is_empty() {
+            app.show_notification(problems.join(" · "));
+        }
+        app
+    }
+
+    // the help popup scrolls; the drawing clamps the scroll to the text
+    fn handle_help_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') => self.show_popup = false,
+            KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Down => self.help_scroll = self.help_scroll.saturating_add(1),
+            KeyCode::Char('p') if ctrl => self.help_scroll = self.help_scroll.saturating_sub(1),
+            KeyCode::Char('n') if ctrl => self.help_scroll = self.help_scroll.saturating_add(1),
+            KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.help_scroll = self.help_scroll.saturating_add(10),
+            KeyCode::Home | KeyCode::Char('f') => self.help_scroll = 0,
+            KeyCode::End | KeyCode::Char('l') => self.help_scroll = u16::MAX,
+            _ => {}
         }
     }
 
@@ -126,20 +334,877 @@ impl App {
         if self.available_themes.is_empty() { return; }
         if let Some(current_idx) = self.available_themes.iter().position(|t| t == &self.current_theme) {
             let next_idx = (current_idx + 1) % self.available_themes.len();
-            self.current_theme = self.available_themes[next_idx].clone();
+            self.apply_theme(&self.available_themes[next_idx].clone());
         }
 
         crate::config::Config::save_config(&self.current_theme);
+        self.theme_changed_time = Some(Instant::now());
+    }
 
-        // recalculate colors after switching
-        if let Some(theme) = self.theme_set.themes.get(&self.current_theme) {
-            self.ui_colors = crate::config::UiColors::from_theme(theme);
+    // switch to a theme and recalculate colors, without saving it
+    fn apply_theme(&mut self, name: &str) {
+        if let Some((theme, preset)) = self.themes.get(name) {
+            let overrides = [preset, self.color_overrides.as_slice()].concat();
+            self.current_theme = name.to_string();
+            (self.ui_colors, _) = crate::config::UiColors::resolve(theme, &overrides, self.color_mode);
         }
+    }
 
-        self.theme_changed_time = Some(Instant::now());
+    // themes whose name contains the picker filter (case-insensitive)
+    pub(crate) fn filtered_themes(&self) -> Vec<&String> {
+        let filter = self.theme_filter.to_lowercase();
+        self.available_themes
+            .iter()
+            .filter(|t| t.to_lowercase().contains(&filter))
+            .collect()
+    }
+
+    fn open_theme_picker(&mut self) {
+        self.theme_picker = true;
+        self.theme_filter.clear();
+        self.theme_before_picker = self.current_theme.clone();
+        let idx = self.available_themes.iter().position(|t| t == &self.current_theme);
+        self.theme_picker_state.select(idx.or(Some(0)));
+    }
+
+    // preview whichever theme is under the picker cursor
+    fn preview_picked_theme(&mut self) {
+        let picked = self
+            .theme_picker_state
+            .selected()
+            .and_then(|i| self.filtered_themes().get(i).map(|t| t.to_string()));
+        if let Some(name) = picked {
+            self.apply_theme(&name);
+        }
+    }
+
+    fn move_theme_cursor(&mut self, down: bool) {
+        let count = self.filtered_themes().len();
+        if count == 0 { return; }
+        let i = match self.theme_picker_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.theme_picker_state.select(Some(i));
+        self.preview_picked_theme();
+    }
+
+    // re-run the filter, keeping the cursor on the first match
+    fn update_theme_filter(&mut self) {
+        let has_matches = !self.filtered_themes().is_empty();
+        self.theme_picker_state.select(if has_matches { Some(0) } else { None });
+        self.preview_picked_theme();
+    }
+
+    fn close_theme_picker(&mut self, commit: bool) {
+        self.theme_picker = false;
+        if commit && self.theme_picker_state.selected().is_some() && !self.filtered_themes().is_empty() {
+            crate::config::Config::save_config(&self.current_theme);
+            self.theme_changed_time = Some(Instant::now());
+        } else {
+            let previous = self.theme_before_picker.clone();
+            self.apply_theme(&previous);
+        }
+    }
+
+    fn open_priority_edit(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) else { return };
+        let name = process.name().to_string_lossy().to_string();
+        self.priority_edit = Some(PriorityEdit::new(process.pid().as_u32(), name));
+    }
+
+    // apply whatever changed in the priority popup; the popup stays open if the value is invalid
+    fn apply_priority_edit(&mut self) {
+        let Some(edit) = self.priority_edit.take() else { return };
+        let mut messages = Vec::new();
+
+        match edit.nice() {
+            Some(nice) if (NICE_MIN..=NICE_MAX).contains(&nice) => {
+                if Some(nice) != edit.original_nice {
+                    match crate::priority::set_nice(edit.pid, nice) {
+                        Ok(()) => messages.push(format!("nice {}", nice)),
+                        Err(e) => messages.push(format!("nice failed: {}", e)),
+                    }
+                }
+            }
+            // the nice value couldn't be read and wasn't typed: leave it alone and apply the I/O priority alone
+            None if edit.original_nice.is_none() && edit.nice_text.is_empty() => {}
+            _ => {
+                self.show_notification(format!("Nice must be between {} and {}", NICE_MIN, NICE_MAX));
+                self.priority_edit = Some(edit);
+                return;
+            }
+        }
+
+        if let Some(io) = edit.io {
+            if Some(io) != edit.original_io {
+                match crate::priority::set_io_priority(edit.pid, io) {
+                    Ok(()) => messages.push(format!("I/O {}", io)),
+                    Err(e) => messages.push(format!("I/O priority failed: {}", e)),
+                }
+            }
+        }
+
+        if !messages.is_empty() {
+            self.show_notification(format!("{} ({}): {}", edit.name, edit.pid, messages.join(", ")));
+        }
+    }
+
+    fn handle_priority_key(&mut self, key: KeyEvent) {
+        let Some(edit) = &mut self.priority_edit else { return };
+        match key.code {
+            KeyCode::Esc => self.priority_edit = None,
+            KeyCode::Enter => self.apply_priority_edit(),
+            KeyCode::Tab | KeyCode::Down => edit.next_field(),
+            KeyCode::Up => edit.prev_field(),
+            KeyCode::Right | KeyCode::Char('+') => edit.step(true),
+            KeyCode::Left => edit.step(false),
+            KeyCode::Char('-') if edit.field == 0 && !edit.nice_fresh => edit.negate(),
+            KeyCode::Char('-') => edit.step(false),
+            KeyCode::Char(c) if c.is_ascii_digit() && edit.field == 0 => edit.type_digit(c),
+            KeyCode::Backspace if edit.field == 0 => edit.backspace(),
+            _ => {}
+        }
+    }
+
+    fn open_affinity_edit(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) else { return };
+        let (pid, name) = (process.pid().as_u32(), process.name().to_string_lossy().to_string());
+        let cpus = crate::affinity::online_cpus(self.s.cpus().len());
+        match crate::affinity::get_affinity(pid, &cpus) {
+            Ok(allowed) => self.affinity_edit = Some(AffinityEdit::new(pid, name, cpus, allowed)),
+            Err(e) => self.show_notification(format!("{} ({}): affinity unavailable: {}", name, pid, e)),
+        }
+    }
+
+    fn apply_affinity_edit(&mut self) {
+        let Some(edit) = self.affinity_edit.take() else { return };
+        if !edit.changed() {
+            return;
+        }
+        match crate::affinity::set_affinity(edit.pid, &edit.cpus, &edit.allowed) {
+            Ok(()) => {
+                let count = edit.allowed.iter().filter(|a| **a).count();
+                self.show_notification(format!("{} ({}): affinity set to {} of {} cores", edit.name, edit.pid, count, edit.allowed.len()));
+            }
+            Err(e) => {
+                self.show_notification(format!("{} ({}): affinity failed: {}", edit.name, edit.pid, e));
+                self.affinity_edit = Some(edit);
+            }
+        }
+    }
+
+    fn handle_affinity_key(&mut self, key: KeyEvent) {
+        let Some(edit) = &mut self.affinity_edit else { return };
+        match key.code {
+            KeyCode::Esc => self.affinity_edit = None,
+            KeyCode::Enter => self.apply_affinity_edit(),
+            KeyCode::Char(' ') => edit.toggle(),
+            KeyCode::Char('a') => edit.toggle_all(),
+            KeyCode::Left => edit.move_cursor(-1, 0),
+            KeyCode::Right => edit.move_cursor(1, 0),
+            KeyCode::Up => edit.move_cursor(0, -1),
+            KeyCode::Down => edit.move_cursor(0, 1),
+            _ => {}
+        }
+    }
+
+    fn open_thread_view(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) else { return };
+        let (pid, name) = (process.pid().as_u32(), process.name().to_string_lossy().to_string());
+        match ThreadView::new(pid, name.clone()) {
+            Ok(view) => self.thread_view = Some(view),
+            Err(e) => self.show_notification(format!("{} ({}): threads unavailable: {}", name, pid, e)),
+        }
+    }
+
+    fn refresh_thread_view(&mut self) {
+        let Some(view) = &mut self.thread_view else { return };
+        if view.refresh().is_err() {
+            let msg = format!("{} ({}) exited", view.name, view.pid);
+            self.thread_view = None;
+            self.show_notification(msg);
+        }
+    }
+
+    fn toggle_net_columns(&mut self) {
+        if self.net_traffic.take().is_some() {
+            return;
+        }
+        let owners = self.socket_owners.get(Duration::from_millis(self.update_freq));
+        match ProcessTraffic::new(owners) {
+            Ok(traffic) => self.net_traffic = Some(traffic),
+            Err(e) => self.show_notification(format!("Network rates unavailable: {}", e)),
+        }
+    }
+
+    fn refresh_net_traffic(&mut self) {
+        let Some(traffic) = &mut self.net_traffic else { return };
+        let owners = self.socket_owners.get(Duration::from_millis(self.update_freq));
+        if let Err(e) = traffic.sample(self.refresh_secs, owners) {
+            self.net_traffic = None;
+            self.show_notification(format!("Network rates unavailable: {}", e));
+        }
+    }
+
+    fn refresh_last_cpus(&mut self) {
+        let queried = self.search_input.text().to_lowercase().contains("core");
+        if !self.show_last_cpu && !queried {
+            self.last_cpus.clear();
+            return;
+        }
+        // a process moves between CPUs all the time, so every one is read again
+        self.last_cpus = self
+            .s
+            .processes()
+            .keys()
+            .filter_map(|pid| procfs::last_cpu(pid.as_u32()).map(|cpu| (*pid, cpu)))
+            .collect();
+    }
+
+    fn refresh_cgroups(&mut self) {
+        let queried = self.search_input.text().contains("cgroup");
+        if self.cgroup_column == CgroupColumn::Off && self.group_filter.is_none() && self.group_view.is_none() && !queried {
+            self.cgroups.clear();
+            return;
+        }
+        // a process rarely changes cgroup, so only new ones are read
+        let processes = self.s.processes();
+        self.cgroups.retain(|pid, _| processes.contains_key(pid));
+        for pid in processes.keys() {
+            if !self.cgroups.contains_key(pid) {
+                if let Some(cgroup) = cgroups::cgroup(pid.as_u32()) {
+                    self.cgroups.insert(*pid, cgroup);
+                }
+            }
+        }
+        if let Some(view) = &mut self.group_view {
+            view.refresh(&self.s, &self.cgroups);
+        }
+    }
+
+    fn open_group_view(&mut self) {
+        self.thread_view = None;
+        self.connection_view = None;
+        self.event_view = None;
+        self.group_view = Some(GroupView::new(&self.s, &HashMap::new()));
+        self.refresh_cgroups();
+    }
+
+    fn handle_group_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = &mut self.group_view else { return };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('C') | KeyCode::Char('q') => self.group_view = None,
+            // limit the process table to the group, or lift the limit if it already is
+            KeyCode::Enter => {
+                if let Some(label) = view.selected().map(|r| r.label.clone()) {
+                    self.group_filter = if self.group_filter.as_ref() == Some(&label) { None } else { Some(label) };
+                    self.group_view = None;
+                    self.table_state.select(Some(0));
+                }
+            }
+            KeyCode::Up => view.move_cursor(false),
+            KeyCode::Down => view.move_cursor(true),
+            KeyCode::Char('p') if ctrl => view.move_cursor(false),
+            KeyCode::Char('n') if ctrl => view.move_cursor(true),
+            KeyCode::Char('f') => view.table_state.select_first(),
+            KeyCode::Char('l') => view.table_state.select_last(),
+            _ => {}
+        }
+    }
+
+    fn open_event_view(&mut self) {
+        self.thread_view = None;
+        self.connection_view = None;
+        self.group_view = None;
+        self.event_view = Some(EventView::default());
     }
-}
 
+    fn handle_event_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = &mut self.event_view else { return };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let count = self.events.events.len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('E') | KeyCode::Char('q') => self.event_view = None,
+            // back to the table with the process selected, if it is still running
+            KeyCode::Enter => {
+                let Some(event) = view.table_state.selected().and_then(|i| self.events.events.iter().rev().nth(i)) else { return };
+                if self.s.process(event.pid).is_some() {
+                    self.jump_to_pid = Some(event.pid);
+                    self.event_view = None;
+                } else {
+                    let msg = format!("{} ({}) is no longer running", event.name, event.pid);
+                    self.show_notification(msg);
+                }
+            }
+            KeyCode::Up => view.move_cursor(false, count),
+            KeyCode::Down => view.move_cursor(true, count),
+            KeyCode::Char('p') if ctrl => view.move_cursor(false, count),
+            KeyCode::Char('n') if ctrl => view.move_cursor(true, count),
+            KeyCode::Char('f') => view.table_state.select_first(),
+            KeyCode::Char('l') => view.table_state.select(Some(count.saturating_sub(1))),
+            _ => {}
+        }
+    }
+
+    fn toggle_pin(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) else { return };
+        let label = format!("{} ({})", process.name().to_string_lossy(), process.pid());
+        let (pinned, patterns) = self.pins.toggle_process(process);
+        let message = if pinned {
+            format!("Pinned {}", label)
+        } else if patterns.is_empty() {
+            format!("Unpinned {}", label)
+        } else {
+            format!("Unpinned {} and patterns {}", label, patterns.join(", "))
+        };
+        self.show_notification(message);
+    }
+
+    fn open_pin_prompt(&mut self) {
+        let mut editor = LineEditor::default();
+        if let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) {
+            editor.set(&process.name().to_string_lossy());
+        }
+        self.pin_prompt = Some(editor);
+    }
+
+    fn handle_pin_prompt_key(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.pin_prompt else { return };
+        match key.code {
+            KeyCode::Esc => self.pin_prompt = None,
+            KeyCode::Enter => {
+                let pattern = editor.text().trim().to_string();
+                self.pin_prompt = None;
+                if pattern.is_empty() {
+                    return;
+                }
+                let verb = if self.pins.toggle_pattern(&pattern) { "Pinned" } else { "Unpinned" };
+                self.show_notification(format!("{} pattern {}", verb, pattern));
+            }
+            _ => {
+                editor.handle_key(key);
+            }
+        }
+    }
+
+    // tag or untag the selected process and move on to the next row
+    fn toggle_tag(&mut self) {
+        let Some(process) = self.selected_pid.and_then(|pid| self.s.process(pid)) else { return };
+        let key = (process.pid(), process.start_time());
+        if !self.tagged.remove(&key) {
+            self.tagged.insert(key);
+        }
+        self.table_state.select(Some(self.table_state.selected().map_or(0, |i| i + 1)));
+    }
+
+    // tag everything the search lists, or untag it if it all already is
+    fn toggle_tag_listed(&mut self) {
+        if self.listed.iter().all(|key| self.tagged.contains(key)) {
+            for key in &self.listed {
+                self.tagged.remove(key);
+            }
+        } else {
+            self.tagged.extend(self.listed.iter().copied());
+        }
+        self.show_notification(format!("{} tagged", self.tagged.len()));
+    }
+
+    // the tagged processes, or the selected one when nothing is tagged
+    fn bulk_targets(&self) -> Vec<&sysinfo::Process> {
+        if self.tagged.is_empty() {
+            return self.selected_pid.and_then(|pid| self.s.process(pid)).into_iter().collect();
+        }
+        let mut targets: Vec<_> = self
+            .tagged
+            .iter()
+            .filter_map(|(pid, start)| self.s.process(*pid).filter(|p| p.start_time() == *start))
+            .collect();
+        targets.sort_by_key(|p| p.pid());
+        targets
+    }
+
+    fn open_bulk_menu(&mut self) {
+        if !self.bulk_targets().is_empty() {
+            self.bulk_menu = Some(BulkMenu::default());
+        }
+    }
+
+    fn handle_bulk_key(&mut self, key: KeyEvent) {
+        let Some(menu) = &mut self.bulk_menu else { return };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        if let Stage::Renice(text) = &mut menu.stage {
+            match key.code {
+                KeyCode::Esc => menu.enter(Stage::Actions),
+                KeyCode::Enter => {
+                    let text = text.clone();
+                    self.bulk_renice(&text);
+                }
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Char(c) if c.is_ascii_digit() || (c == '-' && text.is_empty()) => text.push(c),
+                _ => {}
+            }
+            return;
+        }
+        if let Stage::Confirm(index, _) = menu.stage {
+            match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => self.send_bulk_signal(index),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc | KeyCode::Char('q') => {
+                    menu.enter(Stage::Signals);
+                    menu.table_state.select(Some(index));
+                }
+                _ => {}
+            }
+            return;
+        }
+        let selected = menu.table_state.selected().unwrap_or(0);
+        let signals = matches!(menu.stage, Stage::Signals);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') if signals => menu.enter(Stage::Actions),
+            KeyCode::Esc | KeyCode::Char('q') => self.bulk_menu = None,
+            KeyCode::Up => menu.move_cursor(false),
+            KeyCode::Down => menu.move_cursor(true),
+            KeyCode::Char('p') if ctrl => menu.move_cursor(false),
+            KeyCode::Char('n') if ctrl => menu.move_cursor(true),
+            KeyCode::Enter if signals => self.bulk_signal(selected),
+            KeyCode::Enter => self.bulk_action(bulk::ACTIONS[selected].0),
+            KeyCode::Char(c) if !signals && bulk::ACTIONS.iter().any(|(k, _)| *k == c) => self.bulk_action(c),
+            _ => {}
+        }
+    }
+
+    fn bulk_action(&mut self, action: char) {
+        let Some(menu) = &mut self.bulk_menu else { return };
+        match action {
+            's' => menu.enter(Stage::Signals),
+            'r' => menu.enter(Stage::Renice(String::new())),
+            'x' => {
+                self.bulk_menu = None;
+                let targets = self.bulk_targets();
+                let msg = match bulk::export(&targets, &self.s) {
+                    Ok(path) => format!("Exported {} processes to {}", targets.len(), path.display()),
+                    Err(e) => format!("Export failed: {}", e),
+                };
+                self.show_notification(msg);
+            }
+            'p' => {
+                self.bulk_menu = None;
+                let pids: Vec<Pid> =
+                    self.bulk_targets().into_iter().filter(|p| !self.pins.matches(p)).map(|p| p.pid()).collect();
+                for pid in &pids {
+                    if let Some(process) = self.s.process(*pid) {
+                        self.pins.toggle_process(process);
+                    }
+                }
+                self.show_notification(match pids.len() {
+                    0 => String::from("Pinned nothing new"),
+                    1 => String::from("Pinned 1 process"),
+                    n => format!("Pinned {} processes", n),
+                });
+            }
+            'u' => {
+                self.bulk_menu = None;
+                self.tagged.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn bulk_signal(&mut self, index: usize) {
+        let (signal, _) = bulk::SIGNALS[index];
+        let count = self.bulk_targets().len();
+        if bulk::needs_confirm(signal) && count > 1 {
+            if let Some(menu) = &mut self.bulk_menu {
+                menu.enter(Stage::Confirm(index, count));
+            }
+            return;
+        }
+        self.send_bulk_signal(index);
+    }
+
+    fn send_bulk_signal(&mut self, index: usize) {
+        self.bulk_menu = None;
+        let (signal, name) = bulk::SIGNALS[index];
+        let targets = self.bulk_targets();
+        let count = targets.len();
+        let msg = match bulk::signal(&targets, signal) {
+            (sent, None) => format!("Sent {} to {} of {}", name, sent, count),
+            (sent, Some(e)) => format!("Sent {} to {} of {} ({})", name, sent, count, e),
+        };
+        self.show_notification(msg);
+    }
+
+    fn bulk_renice(&mut self, text: &str) {
+        let nice = match text.parse::<i32>() {
+            Ok(nice) if (NICE_MIN..=NICE_MAX).contains(&nice) => nice,
+            _ => {
+                self.show_notification(format!("Nice must be between {} and {}", NICE_MIN, NICE_MAX));
+                return;
+            }
+        };
+        self.bulk_menu = None;
+        let targets: Vec<u32> = self.bulk_targets().iter().map(|p| p.pid().as_u32()).collect();
+        let mut error = None;
+        let done = targets
+            .iter()
+            .filter(|pid| match crate::priority::set_nice(**pid, nice) {
+                Ok(()) => true,
+                Err(e) => {
+                    error.get_or_insert(e.to_string());
+                    false
+                }
+            })
+            .count();
+        let msg = match error {
+            None => format!("Set nice {} on {} of {}", nice, done, targets.len()),
+            Some(e) => format!("Set nice {} on {} of {} ({})", nice, done, targets.len(), e),
+        };
+        self.show_notification(msg);
+    }
+
+    fn check_alerts(&mut self) {
+        let now = Instant::now();
+        let mut messages = Vec::new();
+        for alert in &mut self.alerts {
+            if let Some(message) = alert.check(&self.s, now) {
+                alert.notify(&message);
+                messages.push(message);
+            }
+        }
+        if !messages.is_empty() {
+            self.show_notification(messages.join(" · "));
+        }
+    }
+
+    // the table as it is now, to save under `name`
+    fn current_view(&self, name: &str) -> SavedView {
+        SavedView {
+            name: name.to_string(),
+            sort_col: self.sort_col,
+            reverse: self.reverse,
+            disk_columns: self.disk_columns,
+            net_columns: self.net_traffic.is_some(),
+            cgroup_column: self.cgroup_column,
+            show_last_cpu: self.show_last_cpu,
+            aggregate: self.aggregate,
+            search_mode: self.search_mode,
+            search_cmdline: self.search_cmdline,
+            filter: self.search_input.text().to_string(),
+        }
+    }
+
+    fn apply_view(&mut self, index: usize) {
+        let Some(view) = self.views.get(index).cloned() else { return };
+        self.sort_col = view.sort_col;
+        self.reverse = view.reverse;
+        self.disk_columns = view.disk_columns;
+        self.cgroup_column = view.cgroup_column;
+        self.show_last_cpu = view.show_last_cpu;
+        self.aggregate = view.aggregate;
+        self.expanded_groups.clear();
+        self.search_mode = view.search_mode;
+        self.search_cmdline = view.search_cmdline;
+        self.search_input.set(&view.filter);
+        self.group_filter = None;
+        if view.net_columns != self.net_traffic.is_some() {
+            self.toggle_net_columns();
+        }
+        self.refresh_cgroups();
+        self.table_state.select(Some(0));
+        self.show_notification(format!("View: {}", view.name));
+    }
+
+    fn save_view(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() || name.contains('=') {
+            self.show_notification(String::from("View names can't be empty or contain '='"));
+            return;
+        }
+        let view = self.current_view(name);
+        Config::save_value(&format!("view.{}", name), &view.to_value());
+        match self.views.iter().position(|v| v.name == name) {
+            Some(i) => self.views[i] = view,
+            None => self.views.push(view),
+        }
+        self.show_notification(format!("Saved view {}", name));
+    }
+
+    fn delete_view(&mut self, index: usize) {
+        if index >= self.views.len() { return; }
+        let view = self.views.remove(index);
+        Config::remove_value(&format!("view.{}", view.name));
+        self.show_notification(format!("Deleted view {}", view.name));
+    }
+
+    // ^r: type to find the newest earlier search containing the text, ^r again for older ones
+    fn handle_history_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = &mut self.history_search else { return };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('r') if ctrl => {
+                if let Some(i) = self.history.search(&search.query, search.found.unwrap_or(usize::MAX)) {
+                    search.found = Some(i);
+                }
+            }
+            KeyCode::Esc => {
+                self.search_input.set(&search.original);
+                self.history_search = None;
+                return;
+            }
+            KeyCode::Char('g') if ctrl => {
+                self.search_input.set(&search.original);
+                self.history_search = None;
+                return;
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.found = self.history.search(&search.query, usize::MAX);
+            }
+            KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                search.query.push(c);
+                search.found = self.history.search(&search.query, search.found.map_or(usize::MAX, |i| i + 1));
+            }
+            // anything else keeps the entry found and goes back to editing
+            _ => {
+                self.history_search = None;
+                return;
+            }
+        }
+        if let Some(text) = search.found.and_then(|i| self.history.get(i)) {
+            self.search_input.set(text);
+        }
+    }
+
+    fn handle_view_picker_key(&mut self, key: KeyEvent) {
+        let Some(picker) = &mut self.view_picker else { return };
+        let plain = !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if let Some(name) = &mut picker.naming {
+            match key.code {
+                KeyCode::Esc => picker.naming = None,
+                KeyCode::Enter => {
+                    let name = name.clone();
+                    self.view_picker = None;
+                    self.save_view(&name);
+                }
+                KeyCode::Backspace => {
+                    name.pop();
+                }
+                KeyCode::Char(c) if plain => name.push(c),
+                _ => {}
+            }
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let count = self.views.len();
+        let selected = picker.table_state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('V') | KeyCode::Char('q') => self.view_picker = None,
+            KeyCode::Enter if count > 0 => {
+                self.view_picker = None;
+                self.apply_view(selected);
+            }
+            KeyCode::Char(c @ '1'..='9') if (c as usize - '1' as usize) < count => {
+                self.view_picker = None;
+                self.apply_view(c as usize - '1' as usize);
+            }
+            KeyCode::Char('s') => picker.naming = Some(String::new()),
+            KeyCode::Char('d') | KeyCode::Delete if count > 0 => {
+                self.delete_view(selected);
+                let count = self.views.len();
+                if let Some(picker) = &mut self.view_picker {
+                    picker.table_state.select(Some(selected.min(count.saturating_sub(1))));
+                }
+            }
+            KeyCode::Up => picker.table_state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Down => picker.table_state.select(Some((selected + 1).min(count.saturating_sub(1)))),
+            KeyCode::Char('p') if ctrl => picker.table_state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Char('n') if ctrl => picker.table_state.select(Some((selected + 1).min(count.saturating_sub(1)))),
+            _ => {}
+        }
+    }
+
+    // Enter on an aggregated row opens or closes it, on a process it shows the details
+    fn activate_row(&mut self) {
+        match self.selected_group.clone() {
+            Some(key) => {
+                if !self.expanded_groups.remove(&key) {
+                    self.expanded_groups.insert(key);
+                }
+            }
+            None => self.process_info = if self.process_info == 0 { 1 } else { 0 },
+        }
+    }
+
+    fn process_name(s: &System, pid: u32) -> Option<String> {
+        s.process(Pid::from_u32(pid)).map(|p| p.name().to_string_lossy().to_string())
+    }
+
+    fn open_connection_view(&mut self) {
+        self.thread_view = None;
+        let owners = self.socket_owners.get(Duration::from_millis(self.update_freq));
+        self.connection_view = Some(ConnectionView::new(owners, |pid| Self::process_name(&self.s, pid)));
+    }
+
+    fn refresh_connection_view(&mut self) {
+        let s = &self.s;
+        if let Some(view) = &mut self.connection_view {
+            let owners = self.socket_owners.get(Duration::from_millis(self.update_freq));
+            view.refresh(owners, |pid| Self::process_name(s, pid));
+        }
+    }
+
+    fn handle_connection_key(&mut self, key: KeyEvent) {
+        let Some(view) = &mut self.connection_view else { return };
+        if view.filter_editing {
+            match key.code {
+                KeyCode::Enter => view.filter_editing = false,
+                KeyCode::Esc => {
+                    view.filter_editing = false;
+                    view.filter.clear();
+                    view.update_rows();
+                }
+                KeyCode::Backspace => {
+                    view.filter.pop();
+                    view.update_rows();
+                }
+                KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                    view.filter.push(c);
+                    view.update_rows();
+                }
+                _ => {}
+            }
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc if !view.filter.is_empty() => {
+                view.filter.clear();
+                view.update_rows();
+            }
+            KeyCode::Esc | KeyCode::Char('N') | KeyCode::Char('q') => self.connection_view = None,
+            KeyCode::Enter => match view.selected().map(|r| (r.pid, r.socket.local)) {
+                Some((Some(pid), _)) => {
+                    self.jump_to_pid = Some(Pid::from_u32(pid));
+                    self.connection_view = None;
+                }
+                Some((None, local)) => self.show_notification(format!("No visible owner for {}", local)),
+                None => {}
+            },
+            KeyCode::Char('/') => view.filter_editing = true,
+            KeyCode::Up => view.move_cursor(false),
+            KeyCode::Down => view.move_cursor(true),
+            KeyCode::Char('p') if ctrl => view.move_cursor(false),
+            KeyCode::Char('n') if ctrl => view.move_cursor(true),
+            KeyCode::Char('f') => view.table_state.select_first(),
+            KeyCode::Char('l') => view.table_state.select_last(),
+            KeyCode::Char('t') => view.sort_by(ConnectionSort::Protocol),
+            KeyCode::Char('o') => view.sort_by(ConnectionSort::Local),
+            KeyCode::Char('r') => view.sort_by(ConnectionSort::Remote),
+            KeyCode::Char('s') => view.sort_by(ConnectionSort::State),
+            KeyCode::Char('p') => view.sort_by(ConnectionSort::Pid),
+            KeyCode::Char('n') => view.sort_by(ConnectionSort::Name),
+            _ => {}
+        }
+    }
+
+    fn handle_thread_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = &mut self.thread_view else { return };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('H') | KeyCode::Char('q') => self.thread_view = None,
+            KeyCode::Up => view.move_cursor(false),
+            KeyCode::Down => view.move_cursor(true),
+            KeyCode::Char('p') if ctrl => view.move_cursor(false),
+            KeyCode::Char('n') if ctrl => view.move_cursor(true),
+            KeyCode::Char('f') => view.table_state.select_first(),
+            KeyCode::Char('l') => view.table_state.select_last(),
+            _ => {}
+        }
+    }
+
+    // open the details view straight on the Files tab
+    fn open_files_view(&mut self) {
+        if self.table_state.selected().is_some() {
+            self.process_info = 1;
+            self.details_tab = crate::details::FILES_TAB;
+            self.details_scroll = 0;
+        }
+    }
+
+    fn handle_details_key(&mut self, key: KeyEvent) {
+        let tabs = crate::details::TABS.len();
+        if self.details_filter_editing {
+            match key.code {
+                KeyCode::Enter => self.details_filter_editing = false,
+                KeyCode::Esc => {
+                    self.details_filter_editing = false;
+                    self.details_filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.details_filter.pop();
+                }
+                KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                    self.details_filter.push(c);
+                    self.details_scroll = 0;
+                }
+                _ => {}
+            }
+            return;
+        }
+        match key.code {
+            KeyCode::Char('/') => self.details_filter_editing = true,
+            KeyCode::Esc if !self.details_filter.is_empty() => self.details_filter.clear(),
+            KeyCode::Enter | KeyCode::Esc | KeyCode::Char('q') => {
+                self.process_info = 0;
+                self.details_filter.clear();
+            }
+            KeyCode::Right | KeyCode::Tab => {
+                self.details_tab = (self.details_tab + 1) % tabs;
+                self.details_scroll = 0;
+            }
+            KeyCode::Left | KeyCode::BackTab => {
+                self.details_tab = (self.details_tab + tabs - 1) % tabs;
+                self.details_scroll = 0;
+            }
+            KeyCode::Char(c @ '1'..='9') if (c as usize - '1' as usize) < tabs => {
+                self.details_tab = c as usize - '1' as usize;
+                self.details_scroll = 0;
+            }
+            KeyCode::Up => self.details_scroll = self.details_scroll.saturating_sub(1),
+            KeyCode::Down => self.details_scroll = self.details_scroll.saturating_add(1),
+            KeyCode::PageUp => self.details_scroll = self.details_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.details_scroll = self.details_scroll.saturating_add(10),
+            KeyCode::Home | KeyCode::Char('f') => self.details_scroll = 0,
+            KeyCode::End | KeyCode::Char('l') => self.details_scroll = u16::MAX,
+            _ => {}
+        }
+    }
+
+    fn handle_theme_picker_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.close_theme_picker(false),
+            KeyCode::Enter => self.close_theme_picker(true),
+            KeyCode::Up => self.move_theme_cursor(false),
+            KeyCode::Down => self.move_theme_cursor(true),
+            KeyCode::Char('p') if ctrl => self.move_theme_cursor(false),
+            KeyCode::Char('n') if ctrl => self.move_theme_cursor(true),
+            KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                self.theme_filter.push(c);
+                self.update_theme_filter();
+            }
+            KeyCode::Backspace if !self.theme_filter.is_empty() => {
+                self.theme_filter.pop();
+                self.update_theme_filter();
+            }
+            _ => {}
+        }
+    }
+}
 
 pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
 
@@ -154,6 +1219,9 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
         }
 
         // refresh system data before drawing
+        let now = Instant::now();
+        app.refresh_secs = now.duration_since(app.last_refresh).as_secs_f64().max(0.001);
+        app.last_refresh = now;
         app.s.refresh_cpu_usage();
         app.s.refresh_memory();
         app.s.refresh_processes_specifics(
@@ -161,6 +1229,17 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
             true,
             ProcessRefreshKind::everything().without_tasks(),
         );
+        app.users.refresh();
+
+        app.refresh_thread_view();
+        app.refresh_connection_view();
+        app.refresh_net_traffic();
+        app.refresh_cgroups();
+        app.refresh_last_cpus();
+        app.check_alerts();
+        app.events.update(&app.s, &app.users, Duration::from_millis(app.update_freq));
+        app.tagged.retain(|(pid, start)| app.s.process(*pid).is_some_and(|p| p.start_time() == *start));
+        app.pins.retain_running(&app.s);
 
         // refresh network data
         app.networks.refresh(true);
@@ -168,16 +1247,52 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
         terminal.draw(|f| ui(f, app)).expect("xtop panic");
 
         if event::poll(Duration::from_millis(app.update_freq))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+            let event = event::read()?;
+            // a paste arrives as one event, so its newlines don't act as Enter
+            if let Event::Paste(text) = &event {
+                if let Some(editor) = &mut app.pin_prompt {
+                    editor.insert_str(text);
+                } else if app.editing && app.history_search.is_none() && app.view_picker.is_none() {
+                    app.search_input.insert_str(text);
+                }
+            }
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press && app.theme_picker {
+                    app.handle_theme_picker_key(key);
+                } else if key.kind == KeyEventKind::Press && app.show_popup {
+                    app.handle_help_key(key);
+                } else if key.kind == KeyEventKind::Press && app.history_search.is_some() {
+                    app.handle_history_search_key(key);
+                } else if key.kind == KeyEventKind::Press && app.pin_prompt.is_some() {
+                    app.handle_pin_prompt_key(key);
+                } else if key.kind == KeyEventKind::Press && app.bulk_menu.is_some() {
+                    app.handle_bulk_key(key);
+                } else if key.kind == KeyEventKind::Press && app.view_picker.is_some() {
+                    app.handle_view_picker_key(key);
+                } else if key.kind == KeyEventKind::Press && app.priority_edit.is_some() {
+                    app.handle_priority_key(key);
+                } else if key.kind == KeyEventKind::Press && app.affinity_edit.is_some() {
+                    app.handle_affinity_key(key);
+                } else if key.kind == KeyEventKind::Press && app.process_info == 1 && !app.editing {
+                    app.handle_details_key(key);
+                } else if key.kind == KeyEventKind::Press && app.group_view.is_some() && !app.editing {
+                    app.handle_group_view_key(key);
+                } else if key.kind == KeyEventKind::Press && app.connection_view.is_some() && !app.editing {
+                    app.handle_connection_key(key);
+                } else if key.kind == KeyEventKind::Press && app.event_view.is_some() && !app.editing {
+                    app.handle_event_view_key(key);
+                } else if key.kind == KeyEventKind::Press && app.thread_view.is_some() && !app.editing {
+                    app.handle_thread_view_key(key);
+                } else if key.kind == KeyEventKind::Press {
                     match app.editing {
                         false => match key.code {
                             KeyCode::Char('q') => return Ok(()),
-                            KeyCode::Char('t') => app.cycle_theme(),
+                            KeyCode::Char('t') => app.open_theme_picker(),
+                            KeyCode::Char('T') => app.cycle_theme(),
                             KeyCode::Char('s') => {
                                 app.editing = true;
+                                app.history.rewind();
                                 app.process_info = 0;
-                                app.cursor_position = app.filter_text.chars().count();
                             }
                             KeyCode::Char('f') => app.table_state.select_first(),
                             KeyCode::Char('l') => app.table_state.select_last(),
@@ -196,15 +1311,11 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
                                 };
                                 app.table_state.select(Some(i));
                             }
-                            KeyCode::Char('-') => {
-                                if app.update_freq > 200 {
-                                    app.update_freq -= 200;
-                                }
+                            KeyCode::Char('-') if app.update_freq > 200 => {
+                                app.update_freq -= 200;
                             }
-                            KeyCode::Char('+') => {
-                                if app.update_freq < 3000 {
-                                    app.update_freq += 200;
-                                }
+                            KeyCode::Char('+') if app.update_freq < 3000 => {
+                                app.update_freq += 200;
                             }
                             KeyCode::Char('p') => {
                                 if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) {
@@ -251,14 +1362,78 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
                                 }
                                 app.sort_col = 3;
                             }
-                            KeyCode::Char('?') => {
-                                app.show_popup = !app.show_popup;
+                            KeyCode::Char('i') => {
+                                app.disk_columns = app.disk_columns.next();
+                                // hiding the disk columns stops sorting by them
+                                if app.disk_columns == DiskColumns::Off && matches!(app.sort_col, 4 | 5) {
+                                    app.sort_col = 3;
+                                    app.reverse = false;
+                                }
+                            }
+                            KeyCode::Char('d') if app.disk_columns != DiskColumns::Off => {
+                                if app.sort_col == app.current_col {
+                                    app.reverse = !app.reverse;
+                                }
+                                app.sort_col = 4;
+                            }
+                            KeyCode::Char('w') if app.disk_columns != DiskColumns::Off => {
+                                if app.sort_col == app.current_col {
+                                    app.reverse = !app.reverse;
+                                }
+                                app.sort_col = 5;
+                            }
+                            KeyCode::Char('b') => app.toggle_net_columns(),
+                            KeyCode::Char('G') => {
+                                app.cgroup_column = app.cgroup_column.next();
+                                app.refresh_cgroups();
+                            }
+                            KeyCode::Char('C') => app.open_group_view(),
+                            KeyCode::Char('E') => app.open_event_view(),
+                            KeyCode::Char('P') => app.toggle_pin(),
+                            KeyCode::Char('K') => app.open_pin_prompt(),
+                            KeyCode::Char(' ') => app.toggle_tag(),
+                            KeyCode::Char('*') => app.toggle_tag_listed(),
+                            KeyCode::Char('x') => app.open_bulk_menu(),
+                            KeyCode::Esc if !app.tagged.is_empty() => app.tagged.clear(),
+                            KeyCode::Char('A') => {
+                                app.aggregate = app.aggregate.next();
+                                app.expanded_groups.clear();
                             }
-                            KeyCode::Enter => {
-                                if app.table_state.selected().is_some() {
-                                    app.process_info = if app.process_info == 0 { 1 } else { 0 };
+                            // drop the filters a view or the containers panel left behind
+                            KeyCode::Esc if app.group_filter.is_some() || !app.search_input.is_empty() => {
+                                app.group_filter = None;
+                                app.search_input.clear();
+                                app.refresh_cgroups();
+                            }
+                            KeyCode::Char('V') => app.view_picker = Some(ViewPicker::default()),
+                            KeyCode::Char(c @ '1'..='9') => app.apply_view(c as usize - '1' as usize),
+                            KeyCode::Char('v') if app.net_traffic.is_some() => {
+                                if app.sort_col == app.current_col {
+                                    app.reverse = !app.reverse;
+                                }
+                                app.sort_col = 6;
+                            }
+                            KeyCode::Char('e') if app.net_traffic.is_some() => {
+                                if app.sort_col == app.current_col {
+                                    app.reverse = !app.reverse;
                                 }
+                                app.sort_col = 7;
+                            }
+                            KeyCode::Char('?') => {
+                                app.show_popup = true;
+                                app.help_scroll = 0;
+                            }
+                            KeyCode::Enter if app.table_state.selected().is_some() => app.activate_row(),
+                            KeyCode::Char('r') => app.open_priority_edit(),
+                            KeyCode::Char('a') => app.open_affinity_edit(),
+                            KeyCode::Char('L') => app.show_last_cpu = !app.show_last_cpu,
+                            KeyCode::Char('F') => {
+                                app.follow = !app.follow;
+                                app.show_notification(String::from(if app.follow { "Following the selected process" } else { "Stopped following" }));
                             }
+                            KeyCode::Char('H') => app.open_thread_view(),
+                            KeyCode::Char('o') => app.open_files_view(),
+                            KeyCode::Char('N') => app.open_connection_view(),
                             KeyCode::Char('u') => {
                                 if app.update_version.is_some() {
                                     let _ = webbrowser::open("https://github.com/mabognar/xtop/releases/latest");
@@ -272,96 +1447,45 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
 
                         true => match key.code {
                             KeyCode::Esc => {
+                                app.history.add(app.search_input.text());
                                 app.editing = false;
-                                app.filter_text.clear();
-                                app.cursor_position = 0;
+                                app.search_input.clear();
                             }
-                            KeyCode::Enter => {
-                                if app.table_state.selected().is_some() {
-                                    app.process_info = if app.process_info == 0 { 1 } else { 0 };
-                                }
-                            }
-                            KeyCode::Char(c) => {
-                                // Handle Ctrl shortcuts
-                                if key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL) {
-                                    if c == 'b' && app.cursor_position > 0 {
-                                        app.cursor_position -= 1;
-                                    } else if c == 'f' && app.cursor_position < app.filter_text.chars().count() {
-                                        app.cursor_position += 1;
-                                    } else if c == 'p' {
-                                        // move up (^p)
-                                        let i = match app.table_state.selected() {
-                                            Some(i) => if i == 0 { 0 } else { i - 1 },
-                                            None => 0,
-                                        };
-                                        app.table_state.select(Some(i));
-                                    } else if c == 'n' {
-                                        // move down (^n)
-                                        let count = app.s.processes().len();
-                                        let i = match app.table_state.selected() {
-                                            Some(i) => if i >= count - 1 { count - 1 } else { i + 1 },
-                                            None => 0,
-                                        };
-                                        app.table_state.select(Some(i));
-                                    }
-                                }
-                                // standard typing
-                                else if !key.modifiers.intersects(crossterm::event::KeyModifiers::CONTROL | crossterm::event::KeyModifiers::ALT) {
-                                    let mut chars: Vec<char> = app.filter_text.chars().collect();
-                                    chars.insert(app.cursor_position, c);
-                                    app.filter_text = chars.into_iter().collect();
-                                    app.cursor_position += 1;
-
-                                    if app.filter_text.is_empty() {
-                                        app.process_info = 0;
-                                    }
-                                }
+                            KeyCode::Enter if app.table_state.selected().is_some() => {
+                                app.history.add(app.search_input.text());
+                                app.activate_row();
                             }
-                            KeyCode::Backspace => {
-                                if app.filter_text.is_empty() {
-                                    app.editing = false;
-                                    app.process_info = 0;
-                                    app.cursor_position = 0;
-                                } else if app.cursor_position > 0 {
-                                    let mut chars: Vec<char> = app.filter_text.chars().collect();
-                                    app.cursor_position -= 1;
-                                    chars.remove(app.cursor_position);
-                                    app.filter_text = chars.into_iter().collect();
-
-                                    if app.filter_text.is_empty() {
-                                        app.process_info = 0;
-                                    }
-                                }
-                            }
-                            KeyCode::Delete => {
-                                if app.cursor_position < app.filter_text.chars().count() {
-                                    let mut chars: Vec<char> = app.filter_text.chars().collect();
-                                    chars.remove(app.cursor_position);
-                                    app.filter_text = chars.into_iter().collect();
-
-                                    if app.filter_text.is_empty() {
-                                        app.process_info = 0;
-                                    }
-                                }
+                            KeyCode::Tab => app.search_mode = app.search_mode.next(),
+                            KeyCode::BackTab => app.search_cmdline = !app.search_cmdline,
+                            KeyCode::Backspace if app.search_input.is_empty() => {
+                                app.editing = false;
+                                app.process_info = 0;
                             }
-                            KeyCode::Left => {
-                                if app.cursor_position > 0 {
-                                    app.cursor_position -= 1;
-                                }
+                            // search the history (^r)
+                            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                app.history_search = Some(HistorySearch {
+                                    query: String::new(),
+                                    found: None,
+                                    original: app.search_input.text().to_string(),
+                                });
                             }
-                            KeyCode::Right => {
-                                if app.cursor_position < app.filter_text.chars().count() {
-                                    app.cursor_position += 1;
-                                }
+                            // tag everything the search matches (^t), and act on the tagged processes (^x)
+                            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => app.toggle_tag_listed(),
+                            KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => app.open_bulk_menu(),
+                            // save the search as a view (^s)
+                            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                app.view_picker = Some(ViewPicker { naming: Some(String::new()), ..Default::default() });
                             }
-                            KeyCode::Up => {
+                            // move up (^p)
+                            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                                 let i = match app.table_state.selected() {
                                     Some(i) => if i == 0 { 0 } else { i - 1 },
                                     None => 0,
                                 };
                                 app.table_state.select(Some(i));
                             }
-                            KeyCode::Down => {
+                            // move down (^n)
+                            KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                                 let count = app.s.processes().len();
                                 let i = match app.table_state.selected() {
                                     Some(i) => if i >= count - 1 { count - 1 } else { i + 1 },
@@ -369,7 +1493,22 @@ pub fn main_loop<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::R
                                 };
                                 app.table_state.select(Some(i));
                             }
-                            _ => {}
+                            // step through earlier searches; ^p/^n still move the table
+                            KeyCode::Up => {
+                                if let Some(text) = app.history.older(app.search_input.text()) {
+                                    app.search_input.set(text);
+                                }
+                            }
+                            KeyCode::Down => {
+                                if let Some(text) = app.history.newer() {
+                                    app.search_input.set(text);
+                                }
+                            }
+                            _ => {
+                                if app.search_input.handle_key(key) && app.search_input.is_empty() {
+                                    app.process_info = 0;
+                                }
+                            }
                         },
                     }
                 }
@@ -0,0 +1,142 @@
+// one action applied to every tagged process: a signal, a new nice value, an export or a pin
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use ratatui::widgets::TableState;
+use serde_json::json;
+use sysinfo::{Process, Signal, System, Users};
+use crate::config::Config;
+use crate::query;
+
+// key, label
+pub const ACTIONS: [(char, &str); 5] = [
+    ('s', "Send signal"),
+    ('r', "Renice"),
+    ('x', "Export details"),
+    ('p', "Pin for this session"),
+    ('u', "Untag all"),
+];
+
+pub const SIGNALS: [(Signal, &str); 8] = [
+    (Signal::Term, "TERM"),
+    (Signal::Kill, "KILL"),
+    (Signal::Hangup, "HUP"),
+    (Signal::Interrupt, "INT"),
+    (Signal::Stop, "STOP"),
+    (Signal::Continue, "CONT"),
+    (Signal::User1, "USR1"),
+    (Signal::User2, "USR2"),
+];
+
+pub enum Stage {
+    Actions,
+    Signals,
+    // the nice value being typed
+    Renice(String),
+    // TERM or KILL for several processes waits for a yes; holds the index into SIGNALS and how many
+    // processes it goes to
+    Confirm(usize, usize),
+}
+
+// state of the bulk action popup
+pub struct BulkMenu {
+    pub stage: Stage,
+    pub table_state: TableState,
+}
+
+impl Default for BulkMenu {
+    fn default() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+        Self { stage: Stage::Actions, table_state }
+    }
+}
+
+impl BulkMenu {
+    pub fn len(&self) -> usize {
+        match self.stage {
+            Stage::Actions => ACTIONS.len(),
+            Stage::Signals => SIGNALS.len(),
+            Stage::Renice(_) | Stage::Confirm(..) => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        let count = self.len();
+        if count == 0 { return; }
+        let i = match self.table_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn enter(&mut self, stage: Stage) {
+        self.stage = stage;
+        self.table_state.select(Some(0));
+    }
+}
+
+// signals that end the process, so asked about before they go to more than one
+pub fn needs_confirm(signal: Signal) -> bool {
+    matches!(signal, Signal::Term | Signal::Kill)
+}
+
+// sends the signal to each process, returning how many got it and the first error
+pub fn signal(processes: &[&Process], signal: Signal) -> (usize, Option<String>) {
+    let mut sent = 0;
+    let mut error = None;
+    for process in processes {
+        match process.kill_with(signal) {
+            Some(true) => sent += 1,
+            Some(false) => {
+                error.get_or_insert_with(|| io::Error::last_os_error().to_string());
+            }
+            None => {
+                error.get_or_insert_with(|| String::from("signal not supported here"));
+            }
+        }
+    }
+    (sent, error)
+}
+
+// writes the processes to ~/.xtop/export-<time>.json
+pub fn export(processes: &[&Process], s: &System) -> io::Result<PathBuf> {
+    let users = Users::new_with_refreshed_list();
+    let rows: Vec<serde_json::Value> = processes
+        .iter()
+        .map(|p| {
+            let user = p
+                .user_id()
+                .map(|uid| users.get_user_by_id(uid).map(|u| u.name().to_string()).unwrap_or(uid.to_string()));
+            json!({
+                "pid": p.pid().as_u32(),
+                "ppid": p.parent().map(|pid| pid.as_u32()),
+                "parent": p.parent().and_then(|pid| s.process(pid)).map(|pp| pp.name().to_string_lossy().to_string()),
+                "name": p.name().to_string_lossy(),
+                "user": user,
+                "state": p.status().to_string(),
+                "cpu": p.cpu_usage(),
+                "memory": p.memory(),
+                "virtual_memory": p.virtual_memory(),
+                "start_time": p.start_time(),
+                "run_time": p.run_time(),
+                "exe": p.exe().map(|e| e.to_string_lossy().to_string()),
+                "command": query::command_line(p),
+            })
+        })
+        .collect();
+    let dir = Config::get_base_dir().ok_or(io::Error::new(io::ErrorKind::NotFound, "no home directory"))?;
+    fs::create_dir_all(&dir)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let path = dir.join(format!("export-{}.json", now));
+    let text = serde_json::to_string_pretty(&rows).map_err(io::Error::other)?;
+    fs::write(&path, text + "\n")?;
+    Ok(path)
+}
@@ -0,0 +1,173 @@
+// which cgroup each process is in, and the container or systemd unit that cgroup belongs to
+use std::collections::HashMap;
+use std::fs;
+use ratatui::widgets::TableState;
+use sysinfo::{Pid, System};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Cgroup {
+    pub path: String,
+    // "docker:web", "k8s:3f2a9c1b7d4e", "nginx.service", or the path when nothing is recognized
+    pub label: String,
+}
+
+// /proc/<pid>/cgroup, preferring the unified (v2) hierarchy and falling back to systemd's v1 one
+pub fn cgroup(pid: u32) -> Option<Cgroup> {
+    let text = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    let entries: Vec<(&str, &str)> = text
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(':')?;
+            rest.split_once(':')
+        })
+        .collect();
+    let path = entries
+        .iter()
+        .find(|(controllers, path)| controllers.is_empty() && *path != "/")
+        .or(entries.iter().find(|(controllers, path)| *controllers == "name=systemd" && *path != "/"))
+        .or(entries.iter().find(|(_, path)| *path != "/"))
+        .map(|(_, path)| path.to_string())
+        .unwrap_or(String::from("/"));
+    Some(Cgroup { label: label(&path), path })
+}
+
+// a short name for the container or unit a cgroup path belongs to
+pub fn label(path: &str) -> String {
+    let components: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+
+    // the innermost component naming a container runtime wins
+    for component in components.iter().rev() {
+        let scope = component.strip_suffix(".scope").unwrap_or(component);
+        for (prefix, runtime) in [
+            ("docker-", "docker"),
+            ("libpod-", "podman"),
+            ("cri-containerd-", "containerd"),
+            ("crio-", "cri-o"),
+        ] {
+            if let Some(id) = scope.strip_prefix(prefix).filter(|id| is_container_id(id)) {
+                if components.iter().any(|c| c.starts_with("kubepods")) {
+                    return format!("k8s:{}", short_id(id));
+                }
+                return format!("{}:{}", runtime, container_name(runtime, id).unwrap_or(short_id(id)));
+            }
+        }
+    }
+    // cgroupfs drivers: /docker/<id>, /kubepods/burstable/pod<uid>/<id>, /lxc/<name>
+    if let Some(i) = components.iter().position(|c| *c == "docker") {
+        if let Some(id) = components.get(i + 1).filter(|id| is_container_id(id)) {
+            return format!("docker:{}", container_name("docker", id).unwrap_or(short_id(id)));
+        }
+    }
+    if components.iter().any(|c| c.starts_with("kubepods")) {
+        if let Some(id) = components.iter().rev().find(|id| is_container_id(id)) {
+            return format!("k8s:{}", short_id(id));
+        }
+        if let Some(pod) = components.iter().rev().find_map(|c| c.split("-pod").nth(1).or(c.strip_prefix("pod"))) {
+            return format!("k8s:pod{}", pod.trim_end_matches(".slice"));
+        }
+    }
+    for component in &components {
+        if let Some(name) = component.strip_prefix("lxc.payload.") {
+            return format!("lxc:{}", name);
+        }
+    }
+    if let Some(i) = components.iter().position(|c| *c == "lxc") {
+        if let Some(name) = components.get(i + 1) {
+            return format!("lxc:{}", name);
+        }
+    }
+
+    // otherwise the systemd unit, leaving out the slices it sits in
+    match components.iter().rev().find(|c| c.ends_with(".service") || c.ends_with(".scope")) {
+        Some(unit) => unit.to_string(),
+        None => components.last().map(|c| c.to_string()).unwrap_or(String::from("/")),
+    }
+}
+
+fn is_container_id(id: &str) -> bool {
+    id.len() >= 32 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn short_id(id: &str) -> String {
+    id.chars().take(12).collect()
+}
+
+// the name given with --name, when the runtime's state is readable
+fn container_name(runtime: &str, id: &str) -> Option<String> {
+    let config = match runtime {
+        "docker" => format!("/var/lib/docker/containers/{}/config.v2.json", id),
+        "podman" => format!("/var/lib/containers/storage/overlay-containers/{}/userdata/config.json", id),
+        _ => return None,
+    };
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(config).ok()?).ok()?;
+    let name = json
+        .get("Name")
+        .or(json.pointer("/annotations/io.podman.annotations.name"))?
+        .as_str()?;
+    Some(name.trim_start_matches('/').to_string())
+}
+
+// one line of the containers panel: the processes sharing a label, added up
+pub struct GroupRow {
+    pub label: String,
+    pub path: String,
+    pub processes: usize,
+    pub cpu_usage: f32,
+    pub memory: u64,
+}
+
+pub struct GroupView {
+    pub rows: Vec<GroupRow>,
+    pub table_state: TableState,
+}
+
+impl GroupView {
+    pub fn new(s: &System, cgroups: &HashMap<Pid, Cgroup>) -> Self {
+        let mut view = Self { rows: Vec::new(), table_state: TableState::default() };
+        view.refresh(s, cgroups);
+        view.table_state.select(Some(0));
+        view
+    }
+
+    pub fn refresh(&mut self, s: &System, cgroups: &HashMap<Pid, Cgroup>) {
+        let selected = self.selected().map(|r| r.label.clone());
+        let mut groups: HashMap<&str, GroupRow> = HashMap::new();
+        for process in s.processes().values() {
+            let Some(cgroup) = cgroups.get(&process.pid()) else { continue };
+            let row = groups.entry(&cgroup.label).or_insert_with(|| GroupRow {
+                label: cgroup.label.clone(),
+                path: cgroup.path.clone(),
+                processes: 0,
+                cpu_usage: 0.0,
+                memory: 0,
+            });
+            row.processes += 1;
+            row.cpu_usage += process.cpu_usage();
+            row.memory += process.memory();
+        }
+        self.rows = groups.into_values().collect();
+        self.rows.sort_by(|a, b| {
+            b.cpu_usage.total_cmp(&a.cpu_usage).then(b.memory.cmp(&a.memory)).then(a.label.cmp(&b.label))
+        });
+
+        let index = selected
+            .and_then(|label| self.rows.iter().position(|r| r.label == label))
+            .or(self.table_state.selected().map(|i| i.min(self.rows.len().saturating_sub(1))));
+        self.table_state.select(index);
+    }
+
+    pub fn selected(&self) -> Option<&GroupRow> {
+        self.table_state.selected().and_then(|i| self.rows.get(i))
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        let count = self.rows.len();
+        if count == 0 { return; }
+        let i = match self.table_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+}
@@ -1,7 +1,7 @@
 use rust_embed::RustEmbed;
 use std::fs;
 use std::path::PathBuf;
-use ratatui::style::Color;
+use ratatui::style::{Color, Modifier, Style};
 use syntect::highlighting::Theme;
 
 // Embed the top-level "themes" directory into the binary
@@ -56,35 +56,254 @@ impl Config {
         Self::get_base_dir().map(|p| p.join("xtoprc"))
     }
 
-    // load the configuration - return the saved theme name
-    pub fn load_config() -> String {
-        let mut theme = String::from("Default-Dark"); // The fallback default
+    // load the configuration (~/.xtop/xtoprc)
+    pub fn load_config() -> Settings {
+        let mut settings = Settings::default();
 
         if let Some(path) = Self::get_config_path() {
             if let Ok(content) = fs::read_to_string(path) {
                 for line in content.lines() {
                     let parts: Vec<&str> = line.splitn(2, '=').collect();
-                    if parts.len() == 2 {
-                        match parts[0].trim() {
-                            "theme" => theme = parts[1].trim().to_string(),
-                            _ => {}
-                        }
+                    if parts.len() != 2 {
+                        continue;
+                    }
+                    let (key, value) = (parts[0].trim(), parts[1].trim());
+                    if key == "theme" {
+                        settings.theme = value.to_string();
+                    } else if key == "color_mode" {
+                        settings.color_mode = value.to_string();
+                    } else if let Some(role) = key.strip_prefix("color.") {
+                        settings.color_overrides.push((role.to_string(), value.to_string()));
+                    } else if let Some(name) = key.strip_prefix("view.") {
+                        settings.views.push((name.to_string(), value.to_string()));
+                    } else if key == "pins" {
+                        settings.pins = value.to_string();
+                    } else if let Some(name) = key.strip_prefix("alert.") {
+                        settings.alerts.push((name.to_string(), value.to_string()));
                     }
                 }
             }
         }
-        theme
+        settings
     }
 
-    // save the current config
+    // save the current theme, keeping every other line of the config intact
     pub fn save_config(theme: &str) {
+        Self::save_value("theme", theme);
+    }
+
+    // replace (or append) a single `key=value` line in the config file
+    pub fn save_value(key: &str, value: &str) {
         if let Some(path) = Self::get_config_path() {
-            let content = format!("theme={}\n", theme);
-            let _ = fs::write(path, content);
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            let mut found = false;
+            let mut lines: Vec<String> = existing
+                .lines()
+                .map(|line| match line.split_once('=') {
+                    Some((k, _)) if k.trim() == key => {
+                        found = true;
+                        format!("{}={}", key, value)
+                    }
+                    _ => line.to_string(),
+                })
+                .collect();
+            if !found {
+                lines.push(format!("{}={}", key, value));
+            }
+            let _ = fs::write(path, lines.join("\n") + "\n");
+        }
+    }
+
+    // drop a `key=value` line from the config file
+    pub fn remove_value(key: &str) {
+        if let Some(path) = Self::get_config_path() {
+            let existing = fs::read_to_string(&path).unwrap_or_default();
+            let lines: Vec<&str> = existing
+                .lines()
+                .filter(|line| !matches!(line.split_once('='), Some((k, _)) if k.trim() == key))
+                .collect();
+            let _ = fs::write(path, lines.join("\n") + "\n");
         }
     }
 }
 
+// everything read from ~/.xtop/xtoprc
+pub struct Settings {
+    pub theme: String,
+    // auto, truecolor, 256, 16 or mono
+    pub color_mode: String,
+    // `color.<role>=<color>` lines, in file order
+    pub color_overrides: Vec<(String, String)>,
+    // `view.<name>=<settings>` lines, in file order
+    pub views: Vec<(String, String)>,
+    // `alert.<name>=<rule>` lines, in file order
+    pub alerts: Vec<(String, String)>,
+    // comma-separated process name patterns
+    pub pins: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: String::from("Default-Dark"), // The fallback default
+            color_mode: String::from("auto"),
+            color_overrides: Vec::new(),
+            views: Vec::new(),
+            alerts: Vec::new(),
+            pins: String::new(),
+        }
+    }
+}
+
+// how many colors the terminal can show
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorMode {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    Mono,
+}
+
+impl ColorMode {
+    // the `color_mode` config value wins; "auto" (or anything unknown) falls back to the environment
+    pub fn from_setting(value: &str) -> Self {
+        match value.to_lowercase().as_str() {
+            "truecolor" | "24bit" => ColorMode::TrueColor,
+            "256" => ColorMode::Ansi256,
+            "16" => ColorMode::Ansi16,
+            "mono" | "none" => ColorMode::Mono,
+            _ => Self::detect(),
+        }
+    }
+
+    // guess from NO_COLOR, COLORTERM and TERM
+    pub fn detect() -> Self {
+        let var = |name: &str| std::env::var(name).unwrap_or_default().to_lowercase();
+
+        if !var("NO_COLOR").is_empty() {
+            return ColorMode::Mono;
+        }
+        let colorterm = var("COLORTERM");
+        if colorterm == "truecolor" || colorterm == "24bit" || !var("WT_SESSION").is_empty() {
+            return ColorMode::TrueColor;
+        }
+        let term = var("TERM");
+        if term.is_empty() {
+            // no TERM at all means a native Windows console, which handles truecolor
+            if cfg!(windows) { ColorMode::TrueColor } else { ColorMode::Ansi16 }
+        } else if term == "dumb" {
+            ColorMode::Mono
+        } else if term.contains("truecolor") || term.contains("direct") {
+            ColorMode::TrueColor
+        } else if term.contains("256") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    // map a color into what this mode can display
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (ColorMode::Mono, _) => Color::Reset,
+            (ColorMode::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_xterm256(r, g, b)),
+            (ColorMode::Ansi16, Color::Rgb(r, g, b)) => rgb_to_ansi16(r, g, b),
+            (ColorMode::Ansi16, Color::Indexed(i)) => {
+                let (r, g, b) = xterm256_to_rgb(i);
+                rgb_to_ansi16(r, g, b)
+            }
+            _ => color,
+        }
+    }
+}
+
+// a background shade `step` away from `bg`, lighter on dark backgrounds
+fn shade(bg: Color, step: u8, lighter: bool) -> Color {
+    match bg {
+        Color::Rgb(r, g, b) if lighter => Color::Rgb(r.saturating_add(step), g.saturating_add(step), b.saturating_add(step)),
+        Color::Rgb(r, g, b) => Color::Rgb(r.saturating_sub(step), g.saturating_sub(step), b.saturating_sub(step)),
+        other => other,
+    }
+}
+
+// text that stands out most against the background: white on dark backgrounds, black on light ones
+fn contrast_text(is_dark: bool) -> Color {
+    if is_dark { Color::Rgb(255, 255, 255) } else { Color::Rgb(0, 0, 0) }
+}
+
+// the six levels of each channel in the xterm 6x6x6 color cube
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn dist2(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+fn xterm256_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI16[i as usize].0,
+        16..=231 => {
+            let i = i - 16;
+            (CUBE_LEVELS[(i / 36) as usize], CUBE_LEVELS[((i / 6) % 6) as usize], CUBE_LEVELS[(i % 6) as usize])
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+// nearest entry of the color cube or the grayscale ramp (16 - 255)
+fn rgb_to_xterm256(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, l)| (**l as i32 - c as i32).abs())
+            .map(|(idx, _)| idx as u8)
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (level(r), level(g), level(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+
+    let avg = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + ((avg.saturating_sub(3)) / 10).min(23) as u8;
+
+    if dist2((r, g, b), xterm256_to_rgb(gray)) < dist2((r, g, b), xterm256_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+// the basic palette, using the common xterm defaults for each entry
+const ANSI16: [((u8, u8, u8), Color); 16] = [
+    ((0, 0, 0), Color::Black),
+    ((205, 0, 0), Color::Red),
+    ((0, 205, 0), Color::Green),
+    ((205, 205, 0), Color::Yellow),
+    ((0, 0, 238), Color::Blue),
+    ((205, 0, 205), Color::Magenta),
+    ((0, 205, 205), Color::Cyan),
+    ((229, 229, 229), Color::Gray),
+    ((127, 127, 127), Color::DarkGray),
+    ((255, 0, 0), Color::LightRed),
+    ((0, 255, 0), Color::LightGreen),
+    ((255, 255, 0), Color::LightYellow),
+    ((92, 92, 255), Color::LightBlue),
+    ((255, 0, 255), Color::LightMagenta),
+    ((0, 255, 255), Color::LightCyan),
+    ((255, 255, 255), Color::White),
+];
+
+fn rgb_to_ansi16(r: u8, g: u8, b: u8) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(rgb, _)| dist2(*rgb, (r, g, b)))
+        .map(|(_, c)| *c)
+        .unwrap_or(Color::Reset)
+}
+
 #[derive(Clone, Copy)]
 pub struct UiColors {
     pub bg: Color,
@@ -93,7 +312,25 @@ pub struct UiColors {
     pub selected_bg: Color,
     pub accent: Color,
     pub title: Color,
+    pub border: Color,
+    pub search_border: Color,
+    // the text in the search box while it is being typed
+    pub search_text: Color,
+    pub popup_border: Color,
+    pub header: Color,
+    pub hot_key: Color,
+    pub muted: Color,
+    pub pipe: Color,
+    pub gauge_low: Color,
+    pub gauge_high: Color,
+    // invalid input, such as a search query that doesn't parse
+    pub error: Color,
+    pub mem_total: Color,
+    pub mem_used: Color,
+    pub mem_avail: Color,
+    pub mem_free: Color,
     pub is_dark: bool,
+    pub mode: ColorMode,
 }
 
 impl UiColors {
@@ -106,17 +343,10 @@ impl UiColors {
 
         let is_dark = (raw_bg.r as u32 + raw_bg.g as u32 + raw_bg.b as u32) < 384;
 
-        let ui_bg = if is_dark {
-            Color::Rgb(raw_bg.r.saturating_add(20), raw_bg.g.saturating_add(20), raw_bg.b.saturating_add(20))
-        } else {
-            Color::Rgb(raw_bg.r.saturating_sub(20), raw_bg.g.saturating_sub(20), raw_bg.b.saturating_sub(20))
-        };
-
-        let selected_bg = if raw_bg.r < 128 {
-            Color::Rgb(raw_bg.r.saturating_add(40), raw_bg.g.saturating_add(40), raw_bg.b.saturating_add(40))
-        } else {
-            Color::Rgb(raw_bg.r.saturating_sub(40), raw_bg.g.saturating_sub(40), raw_bg.b.saturating_sub(40))
-        };
+        let ui_bg = shade(bg, 20, is_dark);
+        let selected_bg = shade(bg, 40, raw_bg.r < 128);
+        // menu separators sit a step further away from the background than the menu bar
+        let pipe = shade(bg, 60, is_dark);
 
         let get_theme_color = |keys: &[&str]| -> Option<Color> {
             for item in &theme.scopes {
@@ -149,7 +379,196 @@ impl UiColors {
             }
         }
 
-        Self { bg, fg, menu_bg: ui_bg, selected_bg, accent, title, is_dark }
+        Self {
+            bg,
+            fg,
+            menu_bg: ui_bg,
+            selected_bg,
+            accent,
+            title,
+            border: accent,
+            search_border: accent,
+            search_text: contrast_text(is_dark),
+            popup_border: accent,
+            header: accent,
+            hot_key: accent,
+            muted: accent,
+            pipe,
+            gauge_low: Color::Rgb(0, 255, 0),
+            gauge_high: Color::Rgb(255, 0, 0),
+            error: Color::Rgb(255, 85, 85),
+            mem_total: Color::Rgb(200, 200, 100),
+            mem_used: Color::Rgb(200, 100, 100),
+            mem_avail: Color::Rgb(100, 200, 100),
+            mem_free: Color::Rgb(50, 255, 255),
+            is_dark,
+            mode: ColorMode::TrueColor,
+        }
+    }
+
+    // theme colors, then user overrides, then reduced to what the terminal can show
+    pub fn resolve(theme: &Theme, overrides: &[(String, String)], mode: ColorMode) -> (Self, Vec<String>) {
+        let mut colors = Self::from_theme(theme);
+        let rejected = colors.apply_overrides(overrides);
+        colors.quantize(mode);
+        (colors, rejected)
+    }
+
+    pub fn quantize(&mut self, mode: ColorMode) {
+        self.mode = mode;
+        for c in [
+            &mut self.bg, &mut self.fg, &mut self.menu_bg, &mut self.selected_bg, &mut self.accent,
+            &mut self.title, &mut self.border, &mut self.search_border, &mut self.search_text, &mut self.popup_border,
+            &mut self.header, &mut self.hot_key, &mut self.muted, &mut self.pipe, &mut self.gauge_low,
+            &mut self.gauge_high, &mut self.error, &mut self.mem_total, &mut self.mem_used,
+            &mut self.mem_avail, &mut self.mem_free,
+        ] {
+            *c = mode.convert(*c);
+        }
+
+        // the subtle background shades collapse into the background with only 16 colors
+        if mode == ColorMode::Ansi16 {
+            let shade = if self.is_dark { Color::DarkGray } else { Color::Gray };
+            if self.selected_bg == self.bg {
+                self.selected_bg = shade;
+            }
+            if self.pipe == self.menu_bg {
+                self.pipe = shade;
+            }
+        }
+    }
+
+    // every role by its `color.<role>` name, for --check-themes
+    pub fn roles(&self) -> [(&'static str, Color); 21] {
+        [
+            ("bg", self.bg), ("fg", self.fg), ("menu_bg", self.menu_bg), ("selected_bg", self.selected_bg),
+            ("accent", self.accent), ("title", self.title), ("border", self.border),
+            ("search_border", self.search_border), ("search_text", self.search_text),
+            ("popup_border", self.popup_border), ("header", self.header), ("hot_key", self.hot_key),
+            ("muted", self.muted), ("pipe", self.pipe), ("gauge_low", self.gauge_low),
+            ("gauge_high", self.gauge_high), ("error", self.error), ("mem_total", self.mem_total),
+            ("mem_used", self.mem_used), ("mem_avail", self.mem_avail), ("mem_free", self.mem_free),
+        ]
+    }
+
+    // style of the selected row; without colors it is shown in reverse video
+    pub fn highlight(&self) -> Style {
+        if self.mode == ColorMode::Mono {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().bg(self.selected_bg)
+        }
+    }
+
+    // apply `color.<role>` overrides from the config, returning the entries that were rejected
+    pub fn apply_overrides(&mut self, overrides: &[(String, String)]) -> Vec<String> {
+        let mut rejected = Vec::new();
+        let mut applied: Vec<&str> = Vec::new();
+        for (role, value) in overrides {
+            let Ok(color) = value.parse::<Color>() else {
+                rejected.push(format!("color.{}={}", role, value));
+                continue;
+            };
+            let slot = match role.as_str() {
+                "bg" => &mut self.bg,
+                "fg" => &mut self.fg,
+                "menu_bg" => &mut self.menu_bg,
+                "selected_bg" => &mut self.selected_bg,
+                "accent" => &mut self.accent,
+                "title" => &mut self.title,
+                "border" => &mut self.border,
+                "search_border" => &mut self.search_border,
+                "search_text" => &mut self.search_text,
+                "popup_border" => &mut self.popup_border,
+                "header" => &mut self.header,
+                "hot_key" => &mut self.hot_key,
+                "muted" => &mut self.muted,
+                "pipe" => &mut self.pipe,
+                "gauge_low" => &mut self.gauge_low,
+                "gauge_high" => &mut self.gauge_high,
+                "error" => &mut self.error,
+                "mem_total" => &mut self.mem_total,
+                "mem_used" => &mut self.mem_used,
+                "mem_avail" => &mut self.mem_avail,
+                "mem_free" => &mut self.mem_free,
+                _ => {
+                    rejected.push(format!("color.{}={}", role, value));
+                    continue;
+                }
+            };
+            *slot = color;
+            applied.push(role);
+        }
+
+        // roles derived from the background or accent follow an override of it, unless set themselves
+        let set = |role: &str| applied.contains(&role);
+        if set("bg") {
+            if let Color::Rgb(r, g, b) = self.bg {
+                self.is_dark = (r as u32 + g as u32 + b as u32) < 384;
+                for (role, slot, step, lighter) in [
+                    ("menu_bg", &mut self.menu_bg, 20, self.is_dark),
+                    ("selected_bg", &mut self.selected_bg, 40, r < 128),
+                    ("pipe", &mut self.pipe, 60, self.is_dark),
+                ] {
+                    if !set(role) {
+                        *slot = shade(self.bg, step, lighter);
+                    }
+                }
+                if !set("search_text") {
+                    self.search_text = contrast_text(self.is_dark);
+                }
+            }
+        }
+        if set("accent") {
+            for (role, slot) in [
+                ("border", &mut self.border),
+                ("search_border", &mut self.search_border),
+                ("popup_border", &mut self.popup_border),
+                ("header", &mut self.header),
+                ("hot_key", &mut self.hot_key),
+                ("muted", &mut self.muted),
+            ] {
+                if !set(role) {
+                    *slot = self.accent;
+                }
+            }
+        }
+        rejected
+    }
+
+    // WCAG contrast ratio between two colors (1.0 - 21.0), if both are known RGB values
+    pub fn contrast(a: Color, b: Color) -> Option<f64> {
+        let luminance = |c: Color| {
+            let (r, g, b) = match c {
+                Color::Rgb(r, g, b) => (r, g, b),
+                Color::Indexed(i) => xterm256_to_rgb(i),
+                _ => return None,
+            };
+            let channel = |v: u8| {
+                let v = v as f64 / 255.0;
+                if v <= 0.03928 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+            };
+            Some(0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b))
+        };
+        let (la, lb) = (luminance(a)?, luminance(b)?);
+        Some((la.max(lb) + 0.05) / (la.min(lb) + 0.05))
     }
-}
 
+    // gauge color for a usage ratio (0.0 - 1.0), blended from gauge_low to gauge_high
+    pub fn gauge(&self, ratio: f32) -> Color {
+        let ratio = ratio.clamp(0.0, 1.0);
+        let to_rgb = |c: Color| match c {
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+            Color::Indexed(i) => Some(xterm256_to_rgb(i)),
+            _ => None,
+        };
+        match (to_rgb(self.gauge_low), to_rgb(self.gauge_high)) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+                let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * ratio) as u8;
+                self.mode.convert(Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2)))
+            }
+            // named colors can't be blended
+            _ => if ratio < 0.5 { self.gauge_low } else { self.gauge_high },
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use ratatui::widgets::TableState;
+use crate::net::{self, Socket};
+
+#[derive(Clone)]
+pub struct ConnectionRow {
+    pub socket: Socket,
+    pub pid: Option<u32>,
+    pub name: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConnectionSort {
+    Protocol,
+    Local,
+    Remote,
+    State,
+    Pid,
+    Name,
+}
+
+// every TCP/UDP socket on the host with the process holding it, sampled on every refresh
+pub struct ConnectionView {
+    // the sampled sockets, and the filtered, sorted ones on screen
+    all: Vec<ConnectionRow>,
+    pub rows: Vec<ConnectionRow>,
+    pub table_state: TableState,
+    pub sort: ConnectionSort,
+    pub reverse: bool,
+    pub filter: String,
+    pub filter_editing: bool,
+}
+
+impl ConnectionView {
+    pub fn new(owners: &HashMap<u64, u32>, names: impl Fn(u32) -> Option<String>) -> Self {
+        let mut view = Self {
+            all: Vec::new(),
+            rows: Vec::new(),
+            table_state: TableState::default(),
+            sort: ConnectionSort::Pid,
+            reverse: false,
+            filter: String::new(),
+            filter_editing: false,
+        };
+        view.refresh(owners, names);
+        view.table_state.select(Some(0));
+        view
+    }
+
+    pub fn refresh(&mut self, owners: &HashMap<u64, u32>, names: impl Fn(u32) -> Option<String>) {
+        self.all = net::sockets("/proc/net")
+            .into_iter()
+            .map(|socket| {
+                // sockets in TIME_WAIT and the like have no inode, and no owner
+                let pid = owners.get(&socket.inode).copied().filter(|_| socket.inode != 0);
+                let name = pid.and_then(&names).unwrap_or_default();
+                ConnectionRow { socket, pid, name }
+            })
+            .collect();
+        self.update_rows();
+    }
+
+    // refilter and resort after a refresh or a change of filter or sort order
+    pub fn update_rows(&mut self) {
+        let selected = self.selected().map(|r| (r.socket.local, r.socket.remote));
+        let filter = self.filter.to_lowercase();
+        self.rows = self.all.iter().filter(|row| row.matches(&filter)).cloned().collect();
+        self.rows.sort_by(|a, b| {
+            let order = match self.sort {
+                ConnectionSort::Protocol => a.socket.protocol.to_string().cmp(&b.socket.protocol.to_string()),
+                ConnectionSort::Local => a.socket.local.cmp(&b.socket.local),
+                ConnectionSort::Remote => a.socket.remote.cmp(&b.socket.remote),
+                ConnectionSort::State => a.socket.state.cmp(b.socket.state),
+                ConnectionSort::Pid => a.pid.cmp(&b.pid),
+                ConnectionSort::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            };
+            order.then(a.socket.local.cmp(&b.socket.local))
+        });
+        if self.reverse {
+            self.rows.reverse();
+        }
+
+        // keep the cursor on the same connection as rows come and go
+        let index = selected
+            .and_then(|ends| self.rows.iter().position(|r| (r.socket.local, r.socket.remote) == ends))
+            .or(self.table_state.selected().map(|i| i.min(self.rows.len().saturating_sub(1))));
+        self.table_state.select(if self.rows.is_empty() { None } else { index.or(Some(0)) });
+    }
+
+    // same column again flips the order, like the process table
+    pub fn sort_by(&mut self, sort: ConnectionSort) {
+        if self.sort == sort {
+            self.reverse = !self.reverse;
+        }
+        self.sort = sort;
+        self.update_rows();
+    }
+
+    pub fn selected(&self) -> Option<&ConnectionRow> {
+        self.table_state.selected().and_then(|i| self.rows.get(i))
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        let count = self.rows.len();
+        if count == 0 { return; }
+        let i = match self.table_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+}
+
+impl ConnectionRow {
+    // `filter` is already lowercase
+    fn matches(&self, filter: &str) -> bool {
+        if filter.is_empty() {
+            return true;
+        }
+        let socket = &self.socket;
+        [
+            socket.protocol.to_string(),
+            socket.local.to_string(),
+            socket.remote.to_string(),
+            socket.state.to_string(),
+            self.pid.map(|p| p.to_string()).unwrap_or_default(),
+            self.name.clone(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(filter))
+    }
+}
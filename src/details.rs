@@ -0,0 +1,196 @@
+// content of the tabs in the Process Details view
+use sysinfo::{Process, System, Users};
+use crate::{net, priority, procfs};
+use crate::ui::{human_bytes, s_to_hms};
+
+pub const TABS: [&str; 6] = ["Overview", "Environment", "Command", "Memory", "Files", "Limits"];
+pub const FILES_TAB: usize = 4;
+
+pub fn overview(process: &Process, s: &System, users: &Users, refresh_secs: f64) -> Vec<(String, String)> {
+    let pid = process.pid().as_u32();
+    let path_or = |p: Option<&std::path::Path>| p.map(|p| p.display().to_string()).unwrap_or("Unknown".to_string());
+
+    let parent = match process.parent() {
+        Some(ppid) => match s.process(ppid) {
+            Some(p) => format!("{} ({})", ppid, p.name().to_string_lossy()),
+            None => ppid.to_string(),
+        },
+        None => String::from("none"),
+    };
+    let user = process
+        .user_id()
+        .and_then(|uid| users.get_user_by_id(uid))
+        .map(|user| user.name().to_string())
+        .unwrap_or("Unknown".to_string());
+    let threads = procfs::status(pid)
+        .into_iter()
+        .find(|(k, _)| k == "Threads")
+        .map(|(_, v)| v)
+        .or(process.tasks().map(|t| t.len().to_string()))
+        .unwrap_or("Unknown".to_string());
+    let (hours, minutes, seconds) = s_to_hms(process.run_time());
+    let disk = process.disk_usage();
+
+    vec![
+        (String::from("PID"), pid.to_string()),
+        (String::from("Parent"), parent),
+        (String::from("User"), user),
+        (String::from("State"), process.status().to_string()),
+        (String::from("Started"), format_timestamp(process.start_time())),
+        (String::from("Run Time"), format!("{}:{:02}:{:02}", hours, minutes, seconds)),
+        (String::from("Threads"), threads),
+        (
+            String::from("Priority"),
+            format!(
+                "nice {}, I/O {}",
+                priority::get_nice(pid).map(|n| n.to_string()).unwrap_or("?".to_string()),
+                priority::get_io_priority(pid).map(|io| io.to_string()).unwrap_or("?".to_string()),
+            ),
+        ),
+        (String::from("CPU"), format!("{:.1}%", process.cpu_usage())),
+        (String::from("Memory"), format!("{:.1} MB", process.memory() as f64 / 1_048_576.0)),
+        (
+            String::from("Disk I/O"),
+            format!(
+                "read {}/s ({} total), write {}/s ({} total)",
+                human_bytes(disk.read_bytes as f64 / refresh_secs),
+                human_bytes(disk.total_read_bytes as f64),
+                human_bytes(disk.written_bytes as f64 / refresh_secs),
+                human_bytes(disk.total_written_bytes as f64),
+            ),
+        ),
+        (String::from("Path"), path_or(process.exe())),
+        (String::from("Cwd"), path_or(process.cwd())),
+        (String::from("Root"), path_or(process.root())),
+    ]
+}
+
+pub fn environment(process: &Process) -> Vec<(String, String)> {
+    let mut vars: Vec<(String, String)> = process
+        .environ()
+        .iter()
+        .map(|var| {
+            let var = var.to_string_lossy();
+            match var.split_once('=') {
+                Some((k, v)) => (k.to_string(), v.to_string()),
+                None => (var.to_string(), String::new()),
+            }
+        })
+        .collect();
+    vars.sort();
+    vars
+}
+
+// the command line as a shell would take it, quoting arguments that need it
+pub fn command(process: &Process) -> String {
+    process
+        .cmd()
+        .iter()
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "'\"$`\\".contains(c)) {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// the Vm* and Rss* lines of /proc/<pid>/status, or what sysinfo knows elsewhere
+pub fn memory(process: &Process) -> Vec<(String, String)> {
+    let fields: Vec<(String, String)> = procfs::status(process.pid().as_u32())
+        .into_iter()
+        .filter(|(k, _)| k.starts_with("Vm") || k.starts_with("Rss") || k == "HugetlbPages")
+        .collect();
+    if !fields.is_empty() {
+        return fields;
+    }
+    vec![
+        (String::from("Resident"), format!("{:.1} MB", process.memory() as f64 / 1_048_576.0)),
+        (String::from("Virtual"), format!("{:.1} MB", process.virtual_memory() as f64 / 1_048_576.0)),
+    ]
+}
+
+// open descriptors as (fd, kind, what it points at), with sockets resolved to addresses
+pub fn files(process: &Process) -> Result<Vec<[String; 3]>, String> {
+    let pid = process.pid().as_u32();
+    let fds = procfs::fds(pid).map_err(|e| e.to_string())?;
+    // /proc/<pid>/net is the process's own network namespace
+    let base = format!("/proc/{}/net", pid);
+    let mut sockets = None;
+    let mut unix_sockets = None;
+
+    Ok(fds
+        .into_iter()
+        .map(|(fd, target)| {
+            let (kind, description) = if let Some(inode) = bracketed(&target, "socket:") {
+                let inode = inode.parse::<u64>().unwrap_or(0);
+                let sockets = sockets.get_or_insert_with(|| net::sockets(&base));
+                match sockets.iter().find(|s| s.inode == inode) {
+                    Some(socket) => (socket.protocol.to_string(), socket.describe()),
+                    None => match unix_sockets.get_or_insert_with(|| net::unix_sockets(&base)).get(&inode) {
+                        Some(path) if !path.is_empty() => (String::from("unix"), path.clone()),
+                        Some(_) => (String::from("unix"), format!("unnamed (inode {})", inode)),
+                        None => (String::from("socket"), format!("inode {}", inode)),
+                    },
+                }
+            } else if let Some(inode) = bracketed(&target, "pipe:") {
+                (String::from("pipe"), format!("inode {}", inode))
+            } else if let Some(name) = target.strip_prefix("anon_inode:") {
+                (String::from("anon"), name.trim_matches(|c| c == '[' || c == ']').to_string())
+            } else if target.starts_with("/dev/") {
+                (String::from("dev"), target)
+            } else if target.starts_with('/') {
+                (String::from("file"), target)
+            } else {
+                (String::from("other"), target)
+            };
+            [fd.to_string(), kind, description]
+        })
+        .collect())
+}
+
+// "socket:[1234]" -> "1234"
+fn bracketed<'a>(target: &'a str, prefix: &str) -> Option<&'a str> {
+    target.strip_prefix(prefix)?.strip_prefix('[')?.strip_suffix(']')
+}
+
+pub fn limits(process: &Process) -> Result<Vec<[String; 4]>, String> {
+    procfs::limits(process.pid().as_u32()).map_err(|e| e.to_string())
+}
+
+// seconds since the epoch as local "YYYY-MM-DD HH:MM:SS"
+#[cfg(unix)]
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let time = secs as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return secs.to_string();
+    }
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min, tm.tm_sec,
+    )
+}
+
+// without libc, fall back to UTC
+#[cfg(not(unix))]
+pub(crate) fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    // civil-from-days, after Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day, (secs / 3600) % 24, (secs / 60) % 60, secs % 60,
+    )
+}
@@ -0,0 +1,316 @@
+// a single-line text input with readline-style keys, used by the search box
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Default)]
+pub struct LineEditor {
+    text: String,
+    // byte offset into `text`, always on a char boundary
+    cursor: usize,
+    // the first char shown when the text is wider than the box
+    scroll: usize,
+    // the text last removed with ^w, ^u or ^k, for ^y
+    killed: String,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    // replace the text, leaving the cursor at the end
+    pub fn set(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+        self.scroll = 0;
+    }
+
+    // pasted text goes in as one line, with newlines and tabs turned into spaces
+    pub fn insert_str(&mut self, text: &str) {
+        let line: String = text
+            .trim_end_matches(['\r', '\n'])
+            .chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c })
+            .filter(|c| !c.is_control())
+            .collect();
+        self.text.insert_str(self.cursor, &line);
+        self.cursor += line.len();
+    }
+
+    // returns false for keys the editor doesn't use
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.text.len(),
+            KeyCode::Char('b') if ctrl => self.cursor = self.prev_char(),
+            KeyCode::Char('f') if ctrl => self.cursor = self.next_char(),
+            KeyCode::Char('b') if alt => self.cursor = self.word_start(),
+            KeyCode::Char('f') if alt => self.cursor = self.word_end(),
+            KeyCode::Left if ctrl || alt => self.cursor = self.word_start(),
+            KeyCode::Right if ctrl || alt => self.cursor = self.word_end(),
+            KeyCode::Left => self.cursor = self.prev_char(),
+            KeyCode::Right => self.cursor = self.next_char(),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.len(),
+            // ^w removes back to the previous space, like a shell
+            KeyCode::Char('w') if ctrl => {
+                let before = self.text[..self.cursor].trim_end_matches(' ');
+                let start = before.rfind(' ').map_or(0, |i| i + 1);
+                self.kill(start, self.cursor);
+            }
+            KeyCode::Backspace if alt => self.kill(self.word_start(), self.cursor),
+            KeyCode::Char('d') if alt => self.kill(self.cursor, self.word_end()),
+            KeyCode::Char('u') if ctrl => self.kill(0, self.cursor),
+            KeyCode::Char('k') if ctrl => self.kill(self.cursor, self.text.len()),
+            KeyCode::Char('y') if ctrl => {
+                let killed = self.killed.clone();
+                self.insert_str(&killed);
+            }
+            KeyCode::Char('h') if ctrl => self.delete(self.prev_char(), self.cursor),
+            KeyCode::Backspace => self.delete(self.prev_char(), self.cursor),
+            KeyCode::Char('d') if ctrl => self.delete(self.cursor, self.next_char()),
+            KeyCode::Delete => self.delete(self.cursor, self.next_char()),
+            KeyCode::Char(c) if !ctrl && !alt => {
+                self.text.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // the part of the text that fits in `width` columns, and where the cursor goes in it;
+    // wide chars (CJK, most emoji) take two columns
+    pub fn visible(&mut self, width: usize) -> (String, usize) {
+        if width == 0 {
+            return (String::new(), 0);
+        }
+        let chars: Vec<char> = self.text.chars().collect();
+        let cursor = self.text[..self.cursor].chars().count();
+        let columns = |chars: &[char]| chars.iter().map(|c| c.width().unwrap_or(0)).sum::<usize>();
+        // don't scroll further than needed, keeping a cell for the cursor past the last char
+        self.scroll = self.scroll.min(chars.len());
+        while self.scroll > 0 && columns(&chars[self.scroll - 1..]) < width {
+            self.scroll -= 1;
+        }
+        if cursor < self.scroll {
+            self.scroll = cursor;
+        }
+        while self.scroll < cursor && columns(&chars[self.scroll..cursor]) >= width {
+            self.scroll += 1;
+        }
+        let mut used = 0;
+        let shown = chars[self.scroll..]
+            .iter()
+            .take_while(|c| {
+                used += c.width().unwrap_or(0);
+                used <= width
+            })
+            .collect();
+        (shown, columns(&chars[self.scroll..cursor]))
+    }
+
+    fn delete(&mut self, start: usize, end: usize) {
+        self.text.replace_range(start..end, "");
+        self.cursor = start;
+    }
+
+    fn kill(&mut self, start: usize, end: usize) {
+        if start < end {
+            self.killed = self.text[start..end].to_string();
+        }
+        self.delete(start, end);
+    }
+
+    fn prev_char(&self) -> usize {
+        self.text[..self.cursor].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    fn next_char(&self) -> usize {
+        self.text[self.cursor..].chars().next().map_or(self.cursor, |c| self.cursor + c.len_utf8())
+    }
+
+    // Alt-b: back to the start of this or the previous word of letters and digits
+    fn word_start(&self) -> usize {
+        let before = &self.text[..self.cursor];
+        let end = before.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+        before[..end].char_indices().rev().find(|(_, c)| !c.is_alphanumeric()).map_or(0, |(i, c)| i + c.len_utf8())
+    }
+
+    // Alt-f: forward to the end of this or the next word
+    fn word_end(&self) -> usize {
+        let after = &self.text[self.cursor..];
+        let start = after.len() - after.trim_start_matches(|c: char| !c.is_alphanumeric()).len();
+        self.cursor + after[start..].find(|c: char| !c.is_alphanumeric()).map_or(after.len(), |i| start + i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(editor: &mut LineEditor, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        editor.handle_key(KeyEvent::new(code, modifiers))
+    }
+
+    fn ctrl(editor: &mut LineEditor, c: char) {
+        press(editor, KeyCode::Char(c), KeyModifiers::CONTROL);
+    }
+
+    fn alt(editor: &mut LineEditor, c: char) {
+        press(editor, KeyCode::Char(c), KeyModifiers::ALT);
+    }
+
+    fn typed(text: &str) -> LineEditor {
+        let mut editor = LineEditor::default();
+        for c in text.chars() {
+            press(&mut editor, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        editor
+    }
+
+    #[test]
+    fn typing_and_moving() {
+        let mut editor = typed("bc");
+        ctrl(&mut editor, 'a');
+        press(&mut editor, KeyCode::Char('a'), KeyModifiers::NONE);
+        ctrl(&mut editor, 'e');
+        press(&mut editor, KeyCode::Char('d'), KeyModifiers::SHIFT);
+        assert_eq!(editor.text(), "abcd");
+        ctrl(&mut editor, 'b');
+        ctrl(&mut editor, 'b');
+        press(&mut editor, KeyCode::Char('-'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "ab-cd");
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        ctrl(&mut editor, 'd');
+        assert_eq!(editor.text(), "b-cd");
+        press(&mut editor, KeyCode::End, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "b-c");
+    }
+
+    #[test]
+    fn multibyte_chars_move_and_delete_whole() {
+        let mut editor = typed("日本語");
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "日語");
+        press(&mut editor, KeyCode::Delete, KeyModifiers::NONE);
+        assert_eq!(editor.text(), "日");
+        press(&mut editor, KeyCode::Right, KeyModifiers::NONE);
+        press(&mut editor, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "日x");
+    }
+
+    #[test]
+    fn word_motion() {
+        let mut editor = typed("cpu > 5 && name:foo");
+        alt(&mut editor, 'b');
+        press(&mut editor, KeyCode::Char('_'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "cpu > 5 && name:_foo");
+        press(&mut editor, KeyCode::Left, KeyModifiers::CONTROL);
+        press(&mut editor, KeyCode::Char('+'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "cpu > 5 && +name:_foo");
+        ctrl(&mut editor, 'a');
+        alt(&mut editor, 'f');
+        alt(&mut editor, 'f');
+        press(&mut editor, KeyCode::Char('0'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "cpu > 50 && +name:_foo");
+        ctrl(&mut editor, 'a');
+        alt(&mut editor, 'd');
+        assert_eq!(editor.text(), " > 50 && +name:_foo");
+    }
+
+    #[test]
+    fn kill_and_yank() {
+        let mut editor = typed("foo bar  ");
+        ctrl(&mut editor, 'w');
+        assert_eq!(editor.text(), "foo ");
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "foo bar  ");
+
+        let mut editor = typed("one two three");
+        alt(&mut editor, 'b');
+        ctrl(&mut editor, 'k');
+        assert_eq!(editor.text(), "one two ");
+        ctrl(&mut editor, 'a');
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "threeone two ");
+        ctrl(&mut editor, 'u');
+        assert_eq!(editor.text(), "one two ");
+        ctrl(&mut editor, 'e');
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "one two three");
+
+        // an empty cut keeps what was cut before
+        ctrl(&mut editor, 'k');
+        ctrl(&mut editor, 'a');
+        ctrl(&mut editor, 'y');
+        assert_eq!(editor.text(), "threeone two three");
+
+        let mut editor = typed("ab cd");
+        press(&mut editor, KeyCode::Backspace, KeyModifiers::ALT);
+        assert_eq!(editor.text(), "ab ");
+    }
+
+    #[test]
+    fn unused_keys_are_passed_on() {
+        let mut editor = typed("a");
+        assert!(!press(&mut editor, KeyCode::Enter, KeyModifiers::NONE));
+        assert!(!press(&mut editor, KeyCode::Char('t'), KeyModifiers::CONTROL));
+        assert!(!press(&mut editor, KeyCode::Up, KeyModifiers::NONE));
+        assert!(press(&mut editor, KeyCode::Char('b'), KeyModifiers::NONE));
+        assert_eq!(editor.text(), "ab");
+    }
+
+    #[test]
+    fn pasted_text_is_one_line() {
+        let mut editor = typed("[]");
+        press(&mut editor, KeyCode::Left, KeyModifiers::NONE);
+        editor.insert_str("a\tb\nc\r\n");
+        assert_eq!(editor.text(), "[a b c]");
+        press(&mut editor, KeyCode::Char('!'), KeyModifiers::NONE);
+        assert_eq!(editor.text(), "[a b c!]");
+    }
+
+    #[test]
+    fn visible_scrolls_to_keep_the_cursor_in_view() {
+        let mut editor = typed("hello");
+        assert_eq!(editor.visible(10), (String::from("hello"), 5));
+        assert_eq!(editor.visible(0), (String::new(), 0));
+
+        let mut editor = typed(&"a".repeat(20));
+        assert_eq!(editor.visible(10), ("a".repeat(9), 9));
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(editor.visible(10), ("a".repeat(10), 0));
+        // moving right inside the box doesn't scroll
+        for _ in 0..5 {
+            press(&mut editor, KeyCode::Right, KeyModifiers::NONE);
+        }
+        assert_eq!(editor.visible(10), ("a".repeat(10), 5));
+    }
+
+    #[test]
+    fn visible_counts_wide_chars_as_two_columns() {
+        let mut editor = typed("日本語テキスト");
+        assert_eq!(editor.visible(6), (String::from("スト"), 4));
+        press(&mut editor, KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(editor.visible(5), (String::from("日本"), 0));
+        press(&mut editor, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(editor.visible(5), (String::from("日本"), 2));
+
+        let mut editor = typed("ab日本");
+        assert_eq!(editor.visible(20), (String::from("ab日本"), 6));
+    }
+}
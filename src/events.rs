@@ -0,0 +1,137 @@
+// process starts and exits, found by comparing the process list between refreshes
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use ratatui::widgets::TableState;
+use sysinfo::{Pid, System, Users};
+
+// the oldest events are dropped past this
+const MAX_EVENTS: usize = 1000;
+// refresh intervals that a new process stays highlighted, and an exited one stays in the table;
+// timed rather than counted, since a keypress also runs an update
+const RECENT_REFRESHES: u32 = 3;
+
+pub struct ProcessEvent {
+    // seconds since the epoch when the refresh noticed it
+    pub time: u64,
+    pub started: bool,
+    pub pid: Pid,
+    pub name: String,
+    pub user: String,
+    // how long an exited process ran, in seconds
+    pub lifetime: Option<u64>,
+}
+
+// a process that has just exited, still shown greyed out in the table
+pub struct ExitedProcess {
+    pub pid: Pid,
+    pub name: String,
+    // its row in the table before it exited
+    pub position: Option<usize>,
+    seen: Instant,
+}
+
+struct Known {
+    name: String,
+    user: String,
+    start_time: u64,
+}
+
+#[derive(Default)]
+pub struct EventLog {
+    // oldest first
+    pub events: Vec<ProcessEvent>,
+    pub exited: Vec<ExitedProcess>,
+    // each process's row in the last plain table, so an exited one can be shown where it was
+    pub positions: HashMap<Pid, usize>,
+    known: HashMap<Pid, Known>,
+    // when each recently started process was first seen
+    started: HashMap<Pid, Instant>,
+    primed: bool,
+}
+
+impl EventLog {
+    // `interval` is the refresh interval, which sets how long recent starts and exits stay marked
+    pub fn update(&mut self, s: &System, users: &Users, interval: Duration) {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let seen = Instant::now();
+        let processes = s.processes();
+        // the first refresh only learns what is already running
+        let first = !self.primed;
+        self.primed = true;
+
+        // a pid reused since the last update has a different start time: the old process exited and a new one started
+        let gone: Vec<Pid> = self
+            .known
+            .iter()
+            .filter(|(pid, known)| processes.get(pid).is_none_or(|p| p.start_time() != known.start_time))
+            .map(|(pid, _)| *pid)
+            .collect();
+        for pid in gone {
+            let Some(known) = self.known.remove(&pid) else { continue };
+            self.started.remove(&pid);
+            let position = self.positions.remove(&pid);
+            self.exited.push(ExitedProcess { pid, name: known.name.clone(), position, seen });
+            self.events.push(ProcessEvent {
+                time: now,
+                started: false,
+                pid,
+                name: known.name,
+                user: known.user,
+                lifetime: Some(now.saturating_sub(known.start_time)),
+            });
+        }
+
+        for (pid, process) in processes {
+            if self.known.contains_key(pid) {
+                continue;
+            }
+            let user = process
+                .user_id()
+                .map(|uid| users.get_user_by_id(uid).map(|u| u.name().to_string()).unwrap_or(uid.to_string()))
+                .unwrap_or(String::from("?"));
+            let name = process.name().to_string_lossy().to_string();
+            if !first {
+                self.started.insert(*pid, seen);
+                self.events.push(ProcessEvent { time: now, started: true, pid: *pid, name: name.clone(), user: user.clone(), lifetime: None });
+            }
+            self.known.insert(*pid, Known { name, user, start_time: process.start_time() });
+        }
+
+        let recent = interval * RECENT_REFRESHES;
+        self.started.retain(|_, t| seen.duration_since(*t) < recent);
+        self.exited.retain(|e| seen.duration_since(e.seen) < recent);
+        if self.events.len() > MAX_EVENTS {
+            self.events.drain(..self.events.len() - MAX_EVENTS);
+        }
+    }
+
+    // started within the last few refresh intervals
+    pub fn is_new(&self, pid: Pid) -> bool {
+        self.started.contains_key(&pid)
+    }
+}
+
+// the event log panel; rows are the log's events, newest first
+pub struct EventView {
+    pub table_state: TableState,
+}
+
+impl Default for EventView {
+    fn default() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+        Self { table_state }
+    }
+}
+
+impl EventView {
+    pub fn move_cursor(&mut self, down: bool, count: usize) {
+        if count == 0 { return; }
+        let i = match self.table_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+}
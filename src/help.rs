@@ -0,0 +1,63 @@
+// the keys listed in the `?` popup; a row with no key starts a new section
+pub const KEYS: &[(&str, &str)] = &[
+    ("", "Process table"),
+    ("↑ ↓  ^p ^n", "Move the selection"),
+    ("f  l", "First or last row"),
+    ("↵", "Details, or open or close a group"),
+    ("p n m c", "Sort by pid, name, memory, cpu; again reverses"),
+    ("s", "Search"),
+    ("-  +", "Refresh faster or slower"),
+    ("t", "Theme picker"),
+    ("T", "Next theme"),
+    ("r", "Renice or set the I/O priority"),
+    ("a", "CPU affinity of the selected process"),
+    ("L", "Show the CPU each process last ran on"),
+    ("H", "Threads of the selected process"),
+    ("i", "Disk I/O columns: rates, totals or off"),
+    ("d  w", "Sort by disk read or write"),
+    ("o", "Open files of the selected process"),
+    ("N", "Network connections"),
+    ("b", "Network rate columns"),
+    ("v  e", "Sort by receive or send rate"),
+    ("G", "Cgroup column: shown, grouped or off"),
+    ("C", "Containers and services"),
+    ("Esc", "Clear the container filter or search"),
+    ("A", "Group by name, user, parent or off"),
+    ("V", "Saved views"),
+    ("1-9", "Apply a saved view"),
+    ("E", "Process start and exit log"),
+    ("P", "Pin or unpin for this session"),
+    ("K", "Pin or unpin a name pattern"),
+    ("Space", "Tag or untag, then move down"),
+    ("*", "Tag or untag every listed process"),
+    ("x", "Act on the tagged or selected processes"),
+    ("Esc", "Clear the tags"),
+    ("F", "Follow the selected process"),
+    ("u", "Open the release page when an update is out"),
+    ("?", "Show or hide this help"),
+    ("q", "Quit"),
+    ("", "Process details"),
+    ("← →  Tab", "Switch tabs"),
+    ("1-9", "Go to a tab"),
+    ("↑ ↓  PgUp PgDn", "Scroll"),
+    ("f  l", "Top or bottom"),
+    ("↵  Esc  q", "Close"),
+    ("/", "Filter the lines"),
+    ("", "Search box"),
+    ("Tab", "Substring, regex or fuzzy matching"),
+    ("⇧Tab", "Also match the command line and path"),
+    ("↵", "Details of the selected process"),
+    ("Esc", "Close the search"),
+    ("^s", "Save the search as a view"),
+    ("↑ ↓", "Earlier or later searches"),
+    ("^r", "Search the history"),
+    ("^p ^n", "Move the selection"),
+    ("^a ^e", "Start or end of the line"),
+    ("^b ^f  ← →", "Back or forward a character"),
+    ("Alt-b Alt-f", "Back or forward a word"),
+    ("^w  Alt-d", "Delete the word before or after"),
+    ("^u ^k", "Delete to the start or end"),
+    ("^y", "Paste the last deletion"),
+    ("^t", "Tag or untag every match"),
+    ("^x", "Act on the tagged processes"),
+];
@@ -0,0 +1,132 @@
+// previous search strings, kept one per line in ~/.xtop/history with the newest last
+use std::fs;
+use crate::config::Config;
+
+const MAX_ENTRIES: usize = 500;
+
+pub struct History {
+    entries: Vec<String>,
+    // the entry shown while stepping with Up/Down; None while editing a fresh line
+    position: Option<usize>,
+    // what was typed before the first Up, given back when stepping down past the newest entry
+    draft: String,
+}
+
+impl History {
+    pub fn load() -> Self {
+        let entries = Config::get_base_dir()
+            .and_then(|dir| fs::read_to_string(dir.join("history")).ok())
+            .map(|text| text.lines().filter(|l| !l.trim().is_empty()).map(String::from).collect())
+            .unwrap_or_default();
+        Self { entries, position: None, draft: String::new() }
+    }
+
+    // remember a search, moving it to the end if it was already there
+    pub fn add(&mut self, text: &str) {
+        self.position = None;
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.entries.retain(|e| e != text);
+        self.entries.push(text.to_string());
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.drain(..self.entries.len() - MAX_ENTRIES);
+        }
+        if let Some(dir) = Config::get_base_dir() {
+            let _ = fs::create_dir_all(&dir);
+            let _ = fs::write(dir.join("history"), self.entries.join("\n") + "\n");
+        }
+    }
+
+    // the entry before the one shown, or None at the oldest
+    pub fn older(&mut self, current: &str) -> Option<&str> {
+        let i = match self.position {
+            Some(0) => return None,
+            Some(i) => i - 1,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len().checked_sub(1)?
+            }
+        };
+        self.position = Some(i);
+        Some(&self.entries[i])
+    }
+
+    // the entry after the one shown, then the draft; None when not stepping through history
+    pub fn newer(&mut self) -> Option<&str> {
+        let i = self.position?;
+        if i + 1 < self.entries.len() {
+            self.position = Some(i + 1);
+            Some(&self.entries[i + 1])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    // start the next Up from the newest entry again
+    pub fn rewind(&mut self) {
+        self.position = None;
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.entries.get(index).map(String::as_str)
+    }
+
+    // the newest entry below `before` containing `query`
+    pub fn search(&self, query: &str, before: usize) -> Option<usize> {
+        self.entries[..before.min(self.entries.len())].iter().rposition(|e| e.contains(query))
+    }
+}
+
+// state of a ^r search through the history
+pub struct HistorySearch {
+    pub query: String,
+    // the entry found, if any
+    pub found: Option<usize>,
+    // the search text before ^r, put back on cancel
+    pub original: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(entries: &[&str]) -> History {
+        History { entries: entries.iter().map(|e| e.to_string()).collect(), position: None, draft: String::new() }
+    }
+
+    #[test]
+    fn search_finds_the_newest_match_first() {
+        let h = history(&["cpu > 5", "firefox", "name ~ /fire/", "postgres"]);
+        assert_eq!(h.search("fire", usize::MAX), Some(2));
+        assert_eq!(h.search("fire", 2), Some(1));
+        assert_eq!(h.search("fire", 1), None);
+        assert_eq!(h.search("", 4), Some(3));
+        assert_eq!(h.search("nothing", 4), None);
+    }
+
+    #[test]
+    fn search_is_case_sensitive_and_handles_an_empty_history() {
+        let h = history(&["Firefox"]);
+        assert_eq!(h.search("fire", 1), None);
+        assert_eq!(h.search("Fire", 1), Some(0));
+        assert_eq!(history(&[]).search("x", 10), None);
+        assert_eq!(history(&["x"]).search("x", 0), None);
+    }
+
+    #[test]
+    fn stepping_gives_back_the_draft() {
+        let mut h = history(&["one", "two"]);
+        assert_eq!(h.newer(), None);
+        assert_eq!(h.older("draft"), Some("two"));
+        assert_eq!(h.older("ignored"), Some("one"));
+        assert_eq!(h.older("ignored"), None);
+        assert_eq!(h.newer(), Some("two"));
+        assert_eq!(h.newer(), Some("draft"));
+        assert_eq!(h.newer(), None);
+        h.rewind();
+        assert_eq!(h.older(""), Some("two"));
+    }
+}
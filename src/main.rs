@@ -1,10 +1,31 @@
 pub mod app;
 pub mod ui;
 pub mod config;
+pub mod theme;
+pub mod priority;
+pub mod affinity;
+pub mod procfs;
+pub mod threads;
+pub mod details;
+pub mod net;
+pub mod connections;
+pub mod cgroups;
+pub mod aggregate;
+pub mod query;
+pub mod search;
+pub mod views;
+pub mod history;
+pub mod editor;
+pub mod alerts;
+pub mod events;
+pub mod pins;
+pub mod bulk;
+pub mod help;
 
 use app::{App, main_loop};
 use config::Config;
 use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -14,10 +35,15 @@ use std::io;
 fn main() -> Result<(), io::Error> {
     let _ = Config::initialize_themes();
 
+    if std::env::args().any(|arg| arg == "--check-themes") {
+        let healthy = theme::check_themes();
+        std::process::exit(if healthy { 0 } else { 1 });
+    }
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -29,7 +55,8 @@ fn main() -> Result<(), io::Error> {
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
-        LeaveAlternateScreen
+        LeaveAlternateScreen,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
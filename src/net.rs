@@ -0,0 +1,329 @@
+// TCP/UDP/unix socket tables from /proc/net, keyed by the inode that shows up
+// as socket:[inode] in /proc/<pid>/fd
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+impl Protocol {
+    const ALL: [Protocol; 4] = [Protocol::Tcp, Protocol::Tcp6, Protocol::Udp, Protocol::Udp6];
+
+    fn file(self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+        }
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.file())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Socket {
+    pub protocol: Protocol,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    pub state: &'static str,
+    pub inode: u64,
+}
+
+impl Socket {
+    // "127.0.0.1:80 -> 10.0.0.2:51234 ESTABLISHED", leaving out the remote end of listeners
+    pub fn describe(&self) -> String {
+        if self.remote.port() == 0 && self.remote.ip().is_unspecified() {
+            format!("{} {}", self.local, self.state)
+        } else {
+            format!("{} -> {} {}", self.local, self.remote, self.state)
+        }
+    }
+}
+
+// every TCP and UDP socket in a network namespace; `base` is /proc/net or /proc/<pid>/net
+pub fn sockets(base: &str) -> Vec<Socket> {
+    let mut sockets = Vec::new();
+    for protocol in Protocol::ALL {
+        let Ok(text) = fs::read_to_string(format!("{}/{}", base, protocol.file())) else { continue };
+        for line in text.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                continue;
+            }
+            let (Some(local), Some(remote)) = (parse_address(fields[1]), parse_address(fields[2])) else { continue };
+            let Ok(inode) = fields[9].parse::<u64>() else { continue };
+            let state = match protocol {
+                Protocol::Tcp | Protocol::Tcp6 => tcp_state(fields[3]),
+                // UDP reuses the TCP codes, but only "connected" (01) means anything
+                Protocol::Udp | Protocol::Udp6 => if fields[3] == "01" { "CONNECTED" } else { "" },
+            };
+            sockets.push(Socket { protocol, local, remote, state, inode });
+        }
+    }
+    sockets
+}
+
+// unix socket inodes and their bound path, if any
+pub fn unix_sockets(base: &str) -> HashMap<u64, String> {
+    let text = fs::read_to_string(format!("{}/unix", base)).unwrap_or_default();
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let inode = fields.get(6)?.parse::<u64>().ok()?;
+            Some((inode, fields.get(7).map(|p| p.to_string()).unwrap_or_default()))
+        })
+        .collect()
+}
+
+// "0100007F:0050" or the 32 hex digit IPv6 form, each 32-bit word in host byte order
+fn parse_address(text: &str) -> Option<SocketAddr> {
+    let (ip_hex, port_hex) = text.split_once(':')?;
+    let port = u16::from_str_radix(port_hex, 16).ok()?;
+    let words: Vec<u32> = (0..ip_hex.len() / 8)
+        .map(|i| u32::from_str_radix(&ip_hex[i * 8..i * 8 + 8], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let ip = match words.as_slice() {
+        [a] => IpAddr::V4(Ipv4Addr::from(a.to_le_bytes())),
+        [a, b, c, d] => {
+            let mut bytes = [0u8; 16];
+            for (i, word) in [a, b, c, d].iter().enumerate() {
+                bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+            }
+            let ip = Ipv6Addr::from(bytes);
+            // show v4-mapped addresses the way people type them
+            match ip.to_ipv4_mapped() {
+                Some(v4) => IpAddr::V4(v4),
+                None => IpAddr::V6(ip),
+            }
+        }
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+fn tcp_state(code: &str) -> &'static str {
+    match code {
+        "01" => "ESTABLISHED",
+        "02" => "SYN_SENT",
+        "03" => "SYN_RECV",
+        "04" => "FIN_WAIT1",
+        "05" => "FIN_WAIT2",
+        "06" => "TIME_WAIT",
+        "07" => "CLOSE",
+        "08" => "CLOSE_WAIT",
+        "09" => "LAST_ACK",
+        "0A" => "LISTEN",
+        "0B" => "CLOSING",
+        _ => "UNKNOWN",
+    }
+}
+
+// socket inode -> owning pid, from the socket:[inode] links under /proc/*/fd
+pub fn socket_owners() -> HashMap<u64, u32> {
+    let mut owners = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else { return owners };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|p| p.parse::<u32>().ok()) else { continue };
+        // other users' descriptors are unreadable without privileges, so those sockets stay unowned
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else { continue };
+        for fd in fds.flatten() {
+            let Ok(target) = fs::read_link(fd.path()) else { continue };
+            let target = target.to_string_lossy();
+            if let Some(inode) = target.strip_prefix("socket:[").and_then(|t| t.strip_suffix(']')) {
+                if let Ok(inode) = inode.parse::<u64>() {
+                    owners.entry(inode).or_insert(pid);
+                }
+            }
+        }
+    }
+    owners
+}
+
+// socket_owners() reads every process's descriptors, so it is kept for a refresh interval
+// rather than redone on every keypress
+#[derive(Default)]
+pub struct SocketOwners {
+    owners: HashMap<u64, u32>,
+    read_at: Option<Instant>,
+}
+
+impl SocketOwners {
+    pub fn get(&mut self, max_age: Duration) -> &HashMap<u64, u32> {
+        if self.read_at.is_none_or(|t| t.elapsed() >= max_age) {
+            self.owners = socket_owners();
+            self.read_at = Some(Instant::now());
+        }
+        &self.owners
+    }
+}
+
+// per-process receive and send rates, from the TCP counters of the sockets each process holds
+pub struct ProcessTraffic {
+    previous: Option<HashMap<u64, (u64, u64)>>,
+    // bytes per second received and sent, by pid
+    rates: HashMap<u32, (f64, f64)>,
+}
+
+impl ProcessTraffic {
+    pub fn new(owners: &HashMap<u64, u32>) -> std::io::Result<Self> {
+        let mut traffic = Self { previous: None, rates: HashMap::new() };
+        traffic.sample(1.0, owners)?;
+        Ok(traffic)
+    }
+
+    // `elapsed` is the time in seconds since the last sample
+    pub fn sample(&mut self, elapsed: f64, owners: &HashMap<u64, u32>) -> std::io::Result<()> {
+        let counters = tcp_traffic()?;
+        self.rates.clear();
+        if let Some(previous) = &self.previous {
+            for (inode, (received, sent)) in &counters {
+                let Some(pid) = owners.get(inode) else { continue };
+                // sockets opened since the last sample count from zero
+                let (received_before, sent_before) = previous.get(inode).copied().unwrap_or((0, 0));
+                let rate = self.rates.entry(*pid).or_insert((0.0, 0.0));
+                rate.0 += received.saturating_sub(received_before) as f64 / elapsed.max(0.001);
+                rate.1 += sent.saturating_sub(sent_before) as f64 / elapsed.max(0.001);
+            }
+        }
+        self.previous = Some(counters);
+        Ok(())
+    }
+
+    pub fn rate(&self, pid: u32) -> (f64, f64) {
+        self.rates.get(&pid).copied().unwrap_or((0.0, 0.0))
+    }
+}
+
+// bytes received and sent by each TCP socket, keyed by inode, from the kernel's tcp_info
+// (the same counters `ss -ti` shows as bytes_received and bytes_acked)
+#[cfg(target_os = "linux")]
+pub fn tcp_traffic() -> std::io::Result<HashMap<u64, (u64, u64)>> {
+    let mut traffic = HashMap::new();
+    for family in [libc::AF_INET, libc::AF_INET6] {
+        sock_diag_dump(family as u8, &mut traffic)?;
+    }
+    Ok(traffic)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn tcp_traffic() -> std::io::Result<HashMap<u64, (u64, u64)>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "per-process network traffic is only available on Linux"))
+}
+
+// values from include/uapi/linux/{netlink,sock_diag,inet_diag}.h
+#[cfg(target_os = "linux")]
+const NETLINK_SOCK_DIAG: libc::c_int = 4;
+#[cfg(target_os = "linux")]
+const SOCK_DIAG_BY_FAMILY: u16 = 20;
+#[cfg(target_os = "linux")]
+const INET_DIAG_INFO: u16 = 2;
+// inet_diag_msg: family, state, timer, retrans, 48 byte socket id, expires, rqueue, wqueue, uid, inode
+#[cfg(target_os = "linux")]
+const INET_DIAG_MSG_LEN: usize = 72;
+#[cfg(target_os = "linux")]
+const INODE_OFFSET: usize = 68;
+// offsets of tcpi_bytes_acked and tcpi_bytes_received in struct tcp_info
+#[cfg(target_os = "linux")]
+const BYTES_ACKED_OFFSET: usize = 120;
+#[cfg(target_os = "linux")]
+const BYTES_RECEIVED_OFFSET: usize = 128;
+
+#[cfg(target_os = "linux")]
+fn sock_diag_dump(family: u8, traffic: &mut HashMap<u64, (u64, u64)>) -> std::io::Result<()> {
+    use std::io::Error;
+
+    let fd = unsafe { libc::socket(libc::AF_NETLINK, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, NETLINK_SOCK_DIAG) };
+    if fd == -1 {
+        return Err(Error::last_os_error());
+    }
+    // close the socket however this returns
+    struct Fd(libc::c_int);
+    impl Drop for Fd {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.0) };
+        }
+    }
+    let fd = Fd(fd);
+
+    // nlmsghdr followed by inet_diag_req_v2 asking for every TCP socket with its tcp_info
+    const REQUEST_LEN: usize = 16 + 56;
+    let mut request = [0u8; REQUEST_LEN];
+    request[0..4].copy_from_slice(&(REQUEST_LEN as u32).to_ne_bytes());
+    request[4..6].copy_from_slice(&SOCK_DIAG_BY_FAMILY.to_ne_bytes());
+    request[6..8].copy_from_slice(&((libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16).to_ne_bytes());
+    request[16] = family;
+    request[17] = libc::IPPROTO_TCP as u8;
+    request[18] = 1 << (INET_DIAG_INFO - 1);
+    request[20..24].copy_from_slice(&u32::MAX.to_ne_bytes());
+    if unsafe { libc::send(fd.0, request.as_ptr().cast(), request.len(), 0) } == -1 {
+        return Err(Error::last_os_error());
+    }
+
+    let mut buffer = vec![0u8; 32 * 1024];
+    loop {
+        let len = unsafe { libc::recv(fd.0, buffer.as_mut_ptr().cast(), buffer.len(), 0) };
+        if len < 0 {
+            return Err(Error::last_os_error());
+        }
+        let mut messages = &buffer[..len as usize];
+        while messages.len() >= 16 {
+            let msg_len = u32::from_ne_bytes(messages[0..4].try_into().unwrap()) as usize;
+            let msg_type = u16::from_ne_bytes(messages[4..6].try_into().unwrap());
+            if msg_len < 16 || msg_len > messages.len() {
+                break;
+            }
+            match msg_type as libc::c_int {
+                libc::NLMSG_DONE => return Ok(()),
+                libc::NLMSG_ERROR => {
+                    let errno = messages.get(16..20).map(|e| i32::from_ne_bytes(e.try_into().unwrap())).unwrap_or(0);
+                    return Err(Error::from_raw_os_error(-errno));
+                }
+                _ => parse_diag_msg(&messages[16..msg_len], traffic),
+            }
+            messages = &messages[align(msg_len).min(messages.len())..];
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn parse_diag_msg(msg: &[u8], traffic: &mut HashMap<u64, (u64, u64)>) {
+    if msg.len() < INET_DIAG_MSG_LEN {
+        return;
+    }
+    let inode = u32::from_ne_bytes(msg[INODE_OFFSET..INODE_OFFSET + 4].try_into().unwrap()) as u64;
+    let mut attributes = &msg[INET_DIAG_MSG_LEN..];
+    while attributes.len() >= 4 {
+        let attr_len = u16::from_ne_bytes(attributes[0..2].try_into().unwrap()) as usize;
+        let attr_type = u16::from_ne_bytes(attributes[2..4].try_into().unwrap());
+        if attr_len < 4 || attr_len > attributes.len() {
+            return;
+        }
+        let info = &attributes[4..attr_len];
+        if attr_type == INET_DIAG_INFO && info.len() >= BYTES_RECEIVED_OFFSET + 8 && inode != 0 {
+            let counter = |at: usize| u64::from_ne_bytes(info[at..at + 8].try_into().unwrap());
+            traffic.insert(inode, (counter(BYTES_RECEIVED_OFFSET), counter(BYTES_ACKED_OFFSET)));
+        }
+        attributes = &attributes[align(attr_len).min(attributes.len())..];
+    }
+}
+
+// netlink messages and attributes are padded to 4 bytes
+#[cfg(target_os = "linux")]
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
@@ -0,0 +1,153 @@
+// pinned processes, kept at the top of the table whatever the sort and search. Name patterns are
+// saved in ~/.xtop/xtoprc as `pins=nginx,postgres*,redis-server`; single processes are pinned for the session.
+use std::collections::HashSet;
+use sysinfo::{Pid, Process, System};
+use crate::config::Config;
+
+#[derive(Default)]
+pub struct Pins {
+    // process names, where `*` matches any run of characters and `?` any one
+    patterns: Vec<String>,
+    // pid and start time, so a reused pid isn't pinned too
+    processes: HashSet<(Pid, u64)>,
+}
+
+impl Pins {
+    pub fn parse(value: &str) -> Self {
+        Self {
+            patterns: value.split(',').map(str::trim).filter(|p| !p.is_empty()).map(String::from).collect(),
+            processes: HashSet::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && self.processes.is_empty()
+    }
+
+    pub fn matches(&self, process: &Process) -> bool {
+        if self.processes.contains(&(process.pid(), process.start_time())) {
+            return true;
+        }
+        let name = process.name().to_string_lossy();
+        self.patterns.iter().any(|p| glob_match(p, &name))
+    }
+
+    // pin just this process, or unpin it along with every pattern matching its name;
+    // returns whether it is now pinned, and the patterns removed
+    pub fn toggle_process(&mut self, process: &Process) -> (bool, Vec<String>) {
+        if !self.matches(process) {
+            self.processes.insert((process.pid(), process.start_time()));
+            return (true, Vec::new());
+        }
+        self.processes.remove(&(process.pid(), process.start_time()));
+        let name = process.name().to_string_lossy();
+        let (removed, kept): (Vec<String>, Vec<String>) = self.patterns.drain(..).partition(|p| glob_match(p, &name));
+        self.patterns = kept;
+        if !removed.is_empty() {
+            self.save();
+        }
+        (false, removed)
+    }
+
+    // add a name pattern, or remove it if it is already there; returns whether it was added
+    pub fn toggle_pattern(&mut self, pattern: &str) -> bool {
+        let added = match self.patterns.iter().position(|p| p == pattern) {
+            Some(i) => {
+                self.patterns.remove(i);
+                false
+            }
+            None => {
+                self.patterns.push(pattern.to_string());
+                true
+            }
+        };
+        self.save();
+        added
+    }
+
+    // forget pinned processes that have exited
+    pub fn retain_running(&mut self, s: &System) {
+        self.processes.retain(|(pid, start)| s.process(*pid).is_some_and(|p| p.start_time() == *start));
+    }
+
+    fn save(&self) {
+        if self.patterns.is_empty() {
+            Config::remove_value("pins");
+        } else {
+            Config::save_value("pins", &self.patterns.join(","));
+        }
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // the position after the last `*`, and the text position it is matching from
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p + 1, t));
+            p += 1;
+        } else if let Some((after, from)) = star {
+            // let the last `*` take one more character
+            p = after;
+            t = from + 1;
+            star = Some((after, from + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_literal_names() {
+        assert!(glob_match("nginx", "nginx"));
+        assert!(!glob_match("nginx", "nginx2"));
+        assert!(!glob_match("nginx", "ngin"));
+        assert!(!glob_match("nginx", "Nginx"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("postgres*", "postgres"));
+        assert!(glob_match("postgres*", "postgres: walwriter"));
+        assert!(!glob_match("postgres*", "pg_postgres"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("*worker*", "kworker/0:1"));
+        assert!(glob_match("python?", "python3"));
+        assert!(!glob_match("python?", "python"));
+        assert!(!glob_match("python?", "python3.12"));
+        assert!(glob_match("?*?", "ab"));
+        assert!(!glob_match("?*?", "a"));
+        assert!(glob_match("日*語", "日本語"));
+    }
+
+    #[test]
+    fn glob_backtracks_to_the_last_star() {
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("*ab", "aaab"));
+        assert!(!glob_match("*ab", "aaba"));
+        assert!(glob_match("a**b", "ab"));
+        assert!(!glob_match("a*b*c", "abcb"));
+    }
+
+    #[test]
+    fn parse_skips_empty_patterns() {
+        let pins = Pins::parse(" nginx, ,postgres* ,,");
+        assert_eq!(pins.patterns, vec!["nginx", "postgres*"]);
+        assert!(!pins.is_empty());
+        assert!(Pins::parse("").is_empty());
+    }
+}
@@ -0,0 +1,266 @@
+use std::fmt;
+use std::io;
+
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
+// Linux I/O scheduling classes, in kernel order
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IoClass {
+    None,
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+impl IoClass {
+    const ALL: [IoClass; 4] = [IoClass::None, IoClass::RealTime, IoClass::BestEffort, IoClass::Idle];
+
+    pub fn step(self, forward: bool) -> Self {
+        let i = Self::ALL.iter().position(|c| *c == self).unwrap_or(0);
+        let n = Self::ALL.len();
+        Self::ALL[if forward { (i + 1) % n } else { (i + n - 1) % n }]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IoClass::None => "none",
+            IoClass::RealTime => "realtime",
+            IoClass::BestEffort => "best-effort",
+            IoClass::Idle => "idle",
+        }
+    }
+
+    // only realtime and best-effort use a level
+    pub fn has_level(self) -> bool {
+        matches!(self, IoClass::RealTime | IoClass::BestEffort)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct IoPriority {
+    pub class: IoClass,
+    // 0 (highest) - 7 (lowest)
+    pub level: u8,
+}
+
+impl fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.class {
+            IoClass::None => write!(f, "none (from nice)"),
+            class if class.has_level() => write!(f, "{} {}", class.name(), self.level),
+            class => write!(f, "{}", class.name()),
+        }
+    }
+}
+
+// only where errno can be cleared, see clear_errno
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+pub fn get_nice(pid: u32) -> io::Result<i32> {
+    // -1 is a valid nice value, so errno is the only way to tell it apart from a failure
+    clear_errno();
+    let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, pid as libc::id_t) };
+    if nice == -1 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error().unwrap_or(0) != 0 {
+            return Err(err);
+        }
+    }
+    Ok(nice)
+}
+
+#[cfg(unix)]
+pub fn set_nice(pid: u32, nice: i32) -> io::Result<()> {
+    let nice = nice.clamp(NICE_MIN, NICE_MAX);
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "openbsd",
+    target_os = "netbsd"
+)))]
+pub fn get_nice(_pid: u32) -> io::Result<i32> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "nice values are not supported on this platform"))
+}
+
+#[cfg(not(unix))]
+pub fn set_nice(_pid: u32, _nice: i32) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "nice values are not supported on this platform"))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn clear_errno() {
+    unsafe { *libc::__errno_location() = 0 }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+fn clear_errno() {
+    unsafe { *libc::__error() = 0 }
+}
+
+#[cfg(any(target_os = "openbsd", target_os = "netbsd"))]
+fn clear_errno() {
+    unsafe { *libc::__errno() = 0 }
+}
+
+// values from include/uapi/linux/ioprio.h
+#[cfg(target_os = "linux")]
+const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const IOPRIO_CLASS_SHIFT: u32 = 13;
+
+#[cfg(target_os = "linux")]
+pub fn get_io_priority(pid: u32) -> io::Result<IoPriority> {
+    let value = unsafe { libc::syscall(libc::SYS_ioprio_get, IOPRIO_WHO_PROCESS, pid as libc::c_int) };
+    if value == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    let value = value as u32;
+    let class = match value >> IOPRIO_CLASS_SHIFT {
+        1 => IoClass::RealTime,
+        2 => IoClass::BestEffort,
+        3 => IoClass::Idle,
+        _ => IoClass::None,
+    };
+    Ok(IoPriority { class, level: (value & 0x7) as u8 })
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_io_priority(pid: u32, priority: IoPriority) -> io::Result<()> {
+    let class: u32 = match priority.class {
+        IoClass::None => 0,
+        IoClass::RealTime => 1,
+        IoClass::BestEffort => 2,
+        IoClass::Idle => 3,
+    };
+    let level = if priority.class.has_level() { priority.level.min(7) as u32 } else { 0 };
+    let value = (class << IOPRIO_CLASS_SHIFT) | level;
+    let result = unsafe {
+        libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, pid as libc::c_int, value as libc::c_int)
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_io_priority(_pid: u32) -> io::Result<IoPriority> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "I/O priority is only available on Linux"))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_io_priority(_pid: u32, _priority: IoPriority) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "I/O priority is only available on Linux"))
+}
+
+// state of the priority popup for one process
+pub struct PriorityEdit {
+    pub pid: u32,
+    pub name: String,
+    // 0 = nice, 1 = I/O class, 2 = I/O level
+    pub field: u8,
+    pub nice_text: String,
+    // true until the user types, so the first digit replaces the shown value
+    pub nice_fresh: bool,
+    pub original_nice: Option<i32>,
+    pub io: Option<IoPriority>,
+    pub original_io: Option<IoPriority>,
+}
+
+impl PriorityEdit {
+    pub fn new(pid: u32, name: String) -> Self {
+        let original_nice = get_nice(pid).ok();
+        let original_io = get_io_priority(pid).ok();
+        Self {
+            pid,
+            name,
+            field: 0,
+            nice_text: original_nice.map(|n| n.to_string()).unwrap_or_default(),
+            nice_fresh: true,
+            original_nice,
+            io: original_io,
+            original_io,
+        }
+    }
+
+    pub fn nice(&self) -> Option<i32> {
+        self.nice_text.parse::<i32>().ok()
+    }
+
+    fn field_count(&self) -> u8 {
+        if self.io.is_some() { 3 } else { 1 }
+    }
+
+    pub fn next_field(&mut self) {
+        self.field = (self.field + 1) % self.field_count();
+    }
+
+    pub fn prev_field(&mut self) {
+        self.field = (self.field + self.field_count() - 1) % self.field_count();
+    }
+
+    // +/- on whichever field has focus
+    pub fn step(&mut self, up: bool) {
+        match self.field {
+            0 => {
+                let nice = self.nice().or(self.original_nice).unwrap_or(0);
+                let nice = if up { nice + 1 } else { nice - 1 };
+                self.nice_text = nice.clamp(NICE_MIN, NICE_MAX).to_string();
+                self.nice_fresh = true;
+            }
+            1 => {
+                if let Some(io) = &mut self.io {
+                    io.class = io.class.step(up);
+                }
+            }
+            _ => {
+                if let Some(io) = &mut self.io {
+                    io.level = if up { (io.level + 1).min(7) } else { io.level.saturating_sub(1) };
+                }
+            }
+        }
+    }
+
+    pub fn type_digit(&mut self, c: char) {
+        if self.nice_fresh {
+            self.nice_text.clear();
+            self.nice_fresh = false;
+        }
+        if self.nice_text.trim_start_matches('-').len() < 2 {
+            self.nice_text.push(c);
+        }
+    }
+
+    // while typing, '-' flips the sign of the typed value
+    pub fn negate(&mut self) {
+        if let Some(rest) = self.nice_text.strip_prefix('-') {
+            self.nice_text = rest.to_string();
+        } else {
+            self.nice_text.insert(0, '-');
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.nice_fresh = false;
+        self.nice_text.pop();
+    }
+}
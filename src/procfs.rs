@@ -0,0 +1,101 @@
+// small readers for Linux /proc files that sysinfo doesn't expose
+use std::fs;
+
+// fields of /proc/<pid>/stat after the parenthesised command name, which may itself contain spaces
+fn stat_fields(path: &str) -> Option<Vec<String>> {
+    let stat = fs::read_to_string(path).ok()?;
+    let after_name = &stat[stat.rfind(')')? + 1..];
+    Some(after_name.split_whitespace().map(String::from).collect())
+}
+
+// CPU the process last ran on (field 39 of /proc/<pid>/stat)
+pub fn last_cpu(pid: u32) -> Option<usize> {
+    // fields are counted from the state, which is field 3
+    stat_fields(&format!("/proc/{}/stat", pid))?.get(39 - 3)?.parse().ok()
+}
+
+// one entry of /proc/<pid>/task
+pub struct TaskStat {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,
+    // utime + stime, in clock ticks
+    pub ticks: u64,
+}
+
+pub fn tasks(pid: u32) -> std::io::Result<Vec<TaskStat>> {
+    let mut tasks = Vec::new();
+    for entry in fs::read_dir(format!("/proc/{}/task", pid))?.flatten() {
+        let Some(tid) = entry.file_name().to_str().and_then(|t| t.parse::<u32>().ok()) else { continue };
+        let path = format!("/proc/{}/task/{}/stat", pid, tid);
+        // threads can exit between listing and reading
+        let Ok(stat) = fs::read_to_string(&path) else { continue };
+        let (Some(open), Some(close)) = (stat.find('('), stat.rfind(')')) else { continue };
+        let fields: Vec<&str> = stat[close + 1..].split_whitespace().collect();
+        let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse::<u64>().ok()).unwrap_or(0);
+        tasks.push(TaskStat {
+            tid,
+            name: stat[open + 1..close].to_string(),
+            state: fields.first().and_then(|s| s.chars().next()).unwrap_or('?'),
+            ticks: field(14) + field(15),
+        });
+    }
+    Ok(tasks)
+}
+
+// kernel clock ticks per second, the unit of the times in /proc/<pid>/stat
+#[cfg(unix)]
+pub fn clock_ticks() -> u64 {
+    match unsafe { libc::sysconf(libc::_SC_CLK_TCK) } {
+        t if t > 0 => t as u64,
+        _ => 100,
+    }
+}
+
+#[cfg(not(unix))]
+pub fn clock_ticks() -> u64 {
+    100
+}
+
+// `Key:  value` lines of /proc/<pid>/status
+pub fn status(pid: u32) -> Vec<(String, String)> {
+    fs::read_to_string(format!("/proc/{}/status", pid))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect()
+}
+
+// open descriptors of a process with what each one points at
+pub fn fds(pid: u32) -> std::io::Result<Vec<(u32, String)>> {
+    let mut fds: Vec<(u32, String)> = fs::read_dir(format!("/proc/{}/fd", pid))?
+        .flatten()
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let target = fs::read_link(entry.path()).ok()?;
+            Some((fd, target.to_string_lossy().to_string()))
+        })
+        .collect();
+    fds.sort_by_key(|(fd, _)| *fd);
+    Ok(fds)
+}
+
+// /proc/<pid>/limits as (limit, soft, hard, units) rows, without the header
+pub fn limits(pid: u32) -> std::io::Result<Vec<[String; 4]>> {
+    let text = fs::read_to_string(format!("/proc/{}/limits", pid))?;
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else { return Ok(Vec::new()) };
+    // the columns are fixed width, so slice at the header's column starts
+    let starts = [
+        0,
+        header.find("Soft Limit").unwrap_or(26),
+        header.find("Hard Limit").unwrap_or(47),
+        header.find("Units").unwrap_or(68),
+    ];
+    let column = |line: &str, i: usize| {
+        let end = starts.get(i + 1).copied().unwrap_or(line.len()).min(line.len());
+        line.get(starts[i].min(end)..end).unwrap_or("").trim().to_string()
+    };
+    Ok(lines.map(|line| [column(line, 0), column(line, 1), column(line, 2), column(line, 3)]).collect())
+}
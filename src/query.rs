@@ -0,0 +1,551 @@
+// filter expressions for the process search box, e.g.
+//   cpu > 5 && user == postgres && name ~ /^pg_/ || cmd:"--replica"
+// anything that doesn't start like an expression is plain text, matched in the chosen search mode
+use std::collections::HashMap;
+use regex::Regex;
+use sysinfo::{Pid, Process, System, Users};
+use crate::cgroups::Cgroup;
+use crate::net::ProcessTraffic;
+use crate::search::{Matcher, SearchMode};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Field {
+    Pid,
+    Ppid,
+    Name,
+    User,
+    Cmd,
+    Path,
+    State,
+    Memory,
+    Cpu,
+    Read,
+    Write,
+    Recv,
+    Sent,
+    Core,
+    Cgroup,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_lowercase().as_str() {
+            "pid" => Field::Pid,
+            "ppid" | "parent" => Field::Ppid,
+            "name" => Field::Name,
+            "user" => Field::User,
+            "cmd" | "command" => Field::Cmd,
+            "path" | "exe" => Field::Path,
+            "state" => Field::State,
+            "memory" | "mem" => Field::Memory,
+            "cpu" => Field::Cpu,
+            "read" => Field::Read,
+            "write" => Field::Write,
+            "recv" => Field::Recv,
+            "sent" => Field::Sent,
+            "core" => Field::Core,
+            "cgroup" => Field::Cgroup,
+            _ => return None,
+        })
+    }
+
+    fn is_text(self) -> bool {
+        matches!(self, Field::Name | Field::User | Field::Cmd | Field::Path | Field::State | Field::Cgroup)
+    }
+
+    // what a bare number means: the unit the table column shows
+    fn default_unit(self) -> f64 {
+        match self {
+            Field::Memory => 1_048_576.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Regex,
+    Contains,
+}
+
+#[derive(Debug)]
+enum Value {
+    Number(f64),
+    Text(String),
+    Regex(Regex),
+}
+
+#[derive(Debug)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Field, Op, Value),
+    // plain text against pid and name, and the command line when searching it
+    Text(Matcher),
+}
+
+#[derive(Debug)]
+pub struct Query(Expr);
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Slashed(String),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+// what the process table needs besides the process itself
+pub struct Context<'a> {
+    pub s: &'a System,
+    pub users: &'a Users,
+    pub cgroups: &'a HashMap<Pid, Cgroup>,
+    pub last_cpus: &'a HashMap<Pid, usize>,
+    pub traffic: Option<&'a ProcessTraffic>,
+    pub refresh_secs: f64,
+    pub search_cmdline: bool,
+}
+
+impl Query {
+    // Err is a message for the search box when the text looks like an expression but isn't one
+    pub fn parse(text: &str, mode: SearchMode) -> Result<Query, String> {
+        if !looks_like_query(text) {
+            return Matcher::new(text, mode).map(|m| Query(Expr::Text(m)));
+        }
+        let mut parser = Parser { tokens: tokenize(text)?, pos: 0 };
+        let expr = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(Query(expr)),
+            Some(token) => Err(format!("unexpected {}", describe(token))),
+        }
+    }
+
+    pub fn matches(&self, p: &Process, ctx: &Context) -> bool {
+        self.0.matches(p, ctx)
+    }
+
+    // the plain-text matcher, for highlighting, when this isn't an expression
+    pub fn text_matcher(&self) -> Option<&Matcher> {
+        match &self.0 {
+            Expr::Text(matcher) => Some(matcher),
+            _ => None,
+        }
+    }
+}
+
+// the full command line, or the name for kernel threads and the like that have none
+pub fn command_line(p: &Process) -> String {
+    let cmd = p.cmd().iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>().join(" ");
+    if cmd.is_empty() { p.name().to_string_lossy().to_string() } else { cmd }
+}
+
+// starts with '(', '!' or a field name and an operator
+fn looks_like_query(text: &str) -> bool {
+    let text = text.trim_start();
+    if text.starts_with('(') || text.starts_with('!') {
+        return true;
+    }
+    let word_len = text.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(text.len());
+    Field::from_name(&text[..word_len]).is_some()
+        && text[word_len..].trim_start().starts_with(|c: char| "=!<>~:".contains(c))
+}
+
+impl Expr {
+    fn matches(&self, p: &Process, ctx: &Context) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(p, ctx) && b.matches(p, ctx),
+            Expr::Or(a, b) => a.matches(p, ctx) || b.matches(p, ctx),
+            Expr::Not(e) => !e.matches(p, ctx),
+            Expr::Text(matcher) => {
+                matcher.is_match(&p.pid().to_string())
+                    || matcher.is_match(&p.name().to_string_lossy())
+                    || (ctx.search_cmdline
+                        && (matcher.is_match(&command_line(p))
+                            || p.exe().is_some_and(|e| matcher.is_match(&e.to_string_lossy()))))
+            }
+            Expr::Compare(field, op, value) if field.is_text() => {
+                let actual = text_field(*field, p, ctx);
+                match (op, value) {
+                    (Op::Regex, Value::Regex(re)) => re.is_match(&actual),
+                    (Op::Contains, Value::Text(t)) => actual.to_lowercase().contains(t.as_str()),
+                    (Op::Eq, Value::Text(t)) => actual.to_lowercase() == *t,
+                    (Op::Ne, Value::Text(t)) => actual.to_lowercase() != *t,
+                    _ => false,
+                }
+            }
+            Expr::Compare(field, op, Value::Number(expected)) => {
+                let Some(actual) = number_field(*field, p, ctx) else { return false };
+                match op {
+                    Op::Eq => actual == *expected,
+                    Op::Ne => actual != *expected,
+                    Op::Gt => actual > *expected,
+                    Op::Ge => actual >= *expected,
+                    Op::Lt => actual < *expected,
+                    Op::Le => actual <= *expected,
+                    _ => false,
+                }
+            }
+            Expr::Compare(..) => false,
+        }
+    }
+}
+
+fn text_field(field: Field, p: &Process, ctx: &Context) -> String {
+    match field {
+        Field::Name => p.name().to_string_lossy().to_string(),
+        Field::User => p
+            .user_id()
+            .and_then(|uid| ctx.users.get_user_by_id(uid))
+            .map(|u| u.name().to_string())
+            .unwrap_or_default(),
+        Field::Cmd => p.cmd().iter().map(|a| a.to_string_lossy()).collect::<Vec<_>>().join(" "),
+        Field::Path => p.exe().map(|e| e.display().to_string()).unwrap_or_default(),
+        Field::State => p.status().to_string(),
+        Field::Cgroup => ctx.cgroups.get(&p.pid()).map(|c| format!("{} {}", c.label, c.path)).unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn number_field(field: Field, p: &Process, ctx: &Context) -> Option<f64> {
+    let disk = p.disk_usage();
+    Some(match field {
+        Field::Pid => p.pid().as_u32() as f64,
+        Field::Ppid => p.parent()?.as_u32() as f64,
+        Field::Memory => p.memory() as f64,
+        Field::Cpu => p.cpu_usage() as f64,
+        Field::Read => disk.read_bytes as f64 / ctx.refresh_secs,
+        Field::Write => disk.written_bytes as f64 / ctx.refresh_secs,
+        Field::Recv => ctx.traffic?.rate(p.pid().as_u32()).0,
+        Field::Sent => ctx.traffic?.rate(p.pid().as_u32()).1,
+        Field::Core => *ctx.last_cpus.get(&p.pid())? as f64,
+        _ => return None,
+    })
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(w) => format!("'{}'", w),
+        Token::Quoted(q) => format!("\"{}\"", q),
+        Token::Slashed(r) => format!("/{}/", r),
+        Token::Op(_) => String::from("operator"),
+        Token::And => String::from("'&&'"),
+        Token::Or => String::from("'||'"),
+        Token::Not => String::from("'!'"),
+        Token::Open => String::from("'('"),
+        Token::Close => String::from("')'"),
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('=', _) => (Token::Op(Op::Eq), 1),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('~', _) => (Token::Op(Op::Regex), 1),
+            (':', _) => (Token::Op(Op::Contains), 1),
+            ('!', _) => (Token::Not, 1),
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            // /.../ only delimits a regex right after ~, elsewhere it's part of a path
+            ('"', _) | ('/', _) if c == '"' || tokens.last() == Some(&Token::Op(Op::Regex)) => {
+                let mut value = String::new();
+                let mut j = i + 1;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(format!("missing closing {}", c)),
+                        Some('\\') if chars.get(j + 1) == Some(&c) => {
+                            value.push(c);
+                            j += 2;
+                        }
+                        Some(d) if *d == c => break,
+                        Some(d) => {
+                            value.push(*d);
+                            j += 1;
+                        }
+                    }
+                }
+                let token = if c == '"' { Token::Quoted(value) } else { Token::Slashed(value) };
+                (token, j + 1 - i)
+            }
+            _ => {
+                let len = chars[i..]
+                    .iter()
+                    .take_while(|d| !d.is_whitespace() && !"&|=!<>~:()\"".contains(**d))
+                    .count();
+                if len == 0 {
+                    return Err(format!("unexpected '{}'", c));
+                }
+                (Token::Word(chars[i..i + len].iter().collect()), len)
+            }
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut query = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            query = Expr::Or(Box::new(query), Box::new(self.and()?));
+        }
+        Ok(query)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut query = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            query = Expr::And(Box::new(query), Box::new(self.unary()?));
+        }
+        Ok(query)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let query = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(query),
+                    _ => Err(String::from("missing ')'")),
+                }
+            }
+            Some(Token::Word(name)) => {
+                let field = Field::from_name(&name).ok_or(format!("unknown field '{}'", name))?;
+                let op = match self.next() {
+                    Some(Token::Op(op)) => op,
+                    _ => return Err(format!("expected an operator after '{}'", name)),
+                };
+                let value = match self.next() {
+                    Some(Token::Word(v)) | Some(Token::Quoted(v)) | Some(Token::Slashed(v)) => v,
+                    Some(token) => return Err(format!("unexpected {}", describe(&token))),
+                    None => return Err(format!("missing value for '{}'", name)),
+                };
+                Ok(Expr::Compare(field, op, compare_value(field, op, &value)?))
+            }
+            Some(token) => Err(format!("unexpected {}", describe(&token))),
+            None => Err(String::from("incomplete expression")),
+        }
+    }
+}
+
+fn compare_value(field: Field, op: Op, value: &str) -> Result<Value, String> {
+    match (field.is_text(), op) {
+        (true, Op::Regex) => Regex::new(value).map(Value::Regex).map_err(|_| format!("bad regex /{}/", value)),
+        (true, Op::Eq | Op::Ne | Op::Contains) => Ok(Value::Text(value.to_lowercase())),
+        (true, _) => Err(String::from("text fields take ==, !=, ~ or :")),
+        (false, Op::Regex | Op::Contains) => Err(String::from("numeric fields take ==, !=, <, <=, > or >=")),
+        (false, _) => parse_number(value, field.default_unit()).map(Value::Number),
+    }
+}
+
+// "5", "1.5G", "200k", "10%"
+fn parse_number(text: &str, default_unit: f64) -> Result<f64, String> {
+    let lower = text.to_lowercase();
+    let split = lower.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(split);
+    let number: f64 = number.parse().map_err(|_| format!("'{}' is not a number", text))?;
+    let unit = match unit.trim_end_matches('b') {
+        "" if unit.is_empty() => default_unit,
+        "" => 1.0,
+        "%" => 1.0,
+        "k" => 1024.0,
+        "m" => 1_048_576.0,
+        "g" => 1_073_741_824.0,
+        "t" => 1_099_511_627_776.0,
+        _ => return Err(format!("unknown unit in '{}'", text)),
+    };
+    Ok(number * unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Expr, String> {
+        Query::parse(text, SearchMode::Substring).map(|q| q.0)
+    }
+
+    #[test]
+    fn tokenize_splits_operators_and_words() {
+        let tokens = tokenize("cpu>=5&&name:foo || !(pid<3)").unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Word("cpu".into()),
+                Token::Op(Op::Ge),
+                Token::Word("5".into()),
+                Token::And,
+                Token::Word("name".into()),
+                Token::Op(Op::Contains),
+                Token::Word("foo".into()),
+                Token::Or,
+                Token::Not,
+                Token::Open,
+                Token::Word("pid".into()),
+                Token::Op(Op::Lt),
+                Token::Word("3".into()),
+                Token::Close,
+            ]
+        );
+        assert_eq!(tokenize("pid = 1").unwrap()[1], Token::Op(Op::Eq));
+        assert_eq!(tokenize("pid != 1").unwrap()[1], Token::Op(Op::Ne));
+    }
+
+    #[test]
+    fn tokenize_quotes_and_slashes() {
+        assert_eq!(tokenize(r#"cmd:"a \"b\"""#).unwrap()[2], Token::Quoted(String::from(r#"a "b""#)));
+        assert_eq!(tokenize("name ~ /^pg_/").unwrap()[2], Token::Slashed(String::from("^pg_")));
+        // a slash that doesn't follow ~ is part of a path
+        assert_eq!(tokenize("path == /usr/bin").unwrap()[2], Token::Word(String::from("/usr/bin")));
+        assert_eq!(tokenize(r#"cmd:"abc"#), Err(String::from("missing closing \"")));
+        assert_eq!(tokenize("name ~ /abc"), Err(String::from("missing closing /")));
+        assert_eq!(tokenize("pid == 1 & pid == 2"), Err(String::from("unexpected '&'")));
+    }
+
+    #[test]
+    fn parse_number_units() {
+        assert_eq!(parse_number("5", 1.0), Ok(5.0));
+        assert_eq!(parse_number("5", 1_048_576.0), Ok(5.0 * 1_048_576.0));
+        assert_eq!(parse_number("-1.5", 1.0), Ok(-1.5));
+        assert_eq!(parse_number("200k", 1_048_576.0), Ok(200.0 * 1024.0));
+        assert_eq!(parse_number("2KB", 1.0), Ok(2048.0));
+        assert_eq!(parse_number("1.5G", 1.0), Ok(1.5 * 1_073_741_824.0));
+        assert_eq!(parse_number("1t", 1.0), Ok(1_099_511_627_776.0));
+        assert_eq!(parse_number("10%", 1.0), Ok(10.0));
+        assert_eq!(parse_number("100b", 1_048_576.0), Ok(100.0));
+        assert!(parse_number("abc", 1.0).is_err());
+        assert!(parse_number("5x", 1.0).is_err());
+        assert!(parse_number("", 1.0).is_err());
+    }
+
+    #[test]
+    fn looks_like_query_needs_a_field_and_an_operator() {
+        assert!(looks_like_query("cpu > 5"));
+        assert!(looks_like_query("cpu>5"));
+        assert!(looks_like_query("  pid:1"));
+        assert!(looks_like_query("Name ~ x"));
+        assert!(looks_like_query("(firefox"));
+        assert!(looks_like_query("!firefox"));
+        assert!(!looks_like_query("firefox"));
+        assert!(!looks_like_query("name"));
+        assert!(!looks_like_query("names == x"));
+        assert!(!looks_like_query("cpu 5"));
+        assert!(!looks_like_query(""));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expr = parse("pid == 1 || pid == 2 && pid == 3").unwrap();
+        let Expr::Or(left, right) = expr else { panic!("expected ||, got {:?}", expr) };
+        assert!(matches!(*left, Expr::Compare(Field::Pid, Op::Eq, Value::Number(n)) if n == 1.0));
+        assert!(matches!(*right, Expr::And(..)));
+
+        let expr = parse("(pid == 1 || pid == 2) && pid == 3").unwrap();
+        let Expr::And(left, _) = expr else { panic!("expected &&, got {:?}", expr) };
+        assert!(matches!(*left, Expr::Or(..)));
+    }
+
+    #[test]
+    fn not_binds_to_the_next_term() {
+        let expr = parse("!pid == 1 && pid == 2").unwrap();
+        let Expr::And(left, right) = expr else { panic!("expected &&, got {:?}", expr) };
+        assert!(matches!(*left, Expr::Not(_)));
+        assert!(matches!(*right, Expr::Compare(Field::Pid, ..)));
+
+        let expr = parse("!!(cpu > 5)").unwrap();
+        let Expr::Not(inner) = expr else { panic!("expected !, got {:?}", expr) };
+        assert!(matches!(*inner, Expr::Not(_)));
+    }
+
+    #[test]
+    fn valid_expressions() {
+        let number = |expr| match expr {
+            Expr::Compare(field, op, Value::Number(n)) => (field, op, n),
+            other => panic!("expected a number, got {:?}", other),
+        };
+        assert_eq!(number(parse("memory > 1.5G").unwrap()), (Field::Memory, Op::Gt, 1.5 * 1_073_741_824.0));
+        assert_eq!(number(parse("mem >= 100").unwrap()), (Field::Memory, Op::Ge, 100.0 * 1_048_576.0));
+        let text = |expr| match expr {
+            Expr::Compare(_, _, Value::Text(t)) => t,
+            other => panic!("expected text, got {:?}", other),
+        };
+        assert_eq!(text(parse("user == Postgres").unwrap()), "postgres");
+        assert_eq!(text(parse(r#"cmd:"--replica""#).unwrap()), "--replica");
+        assert!(matches!(parse("name ~ /^pg_/").unwrap(), Expr::Compare(Field::Name, Op::Regex, Value::Regex(_))));
+        assert!(matches!(parse("parent == 1").unwrap(), Expr::Compare(Field::Ppid, ..)));
+        assert!(parse("cpu > 5 && user == postgres && name ~ /^pg_/ || cmd:\"--replica\"").is_ok());
+    }
+
+    #[test]
+    fn plain_text_is_not_an_expression() {
+        let query = Query::parse("firefox", SearchMode::Substring).unwrap();
+        assert!(query.text_matcher().is_some());
+        let query = Query::parse("cpu > 5", SearchMode::Substring).unwrap();
+        assert!(query.text_matcher().is_none());
+    }
+
+    #[test]
+    fn invalid_expressions() {
+        let error = |text| parse(text).unwrap_err();
+        assert_eq!(error("cpu > abc"), "'abc' is not a number");
+        assert_eq!(error("cpu > 5q"), "unknown unit in '5q'");
+        assert_eq!(error("name > 5"), "text fields take ==, !=, ~ or :");
+        assert_eq!(error("cpu ~ 5"), "numeric fields take ==, !=, <, <=, > or >=");
+        assert_eq!(error("name ~ /[/"), "bad regex /[/");
+        assert_eq!(error("(pid == 1"), "missing ')'");
+        assert_eq!(error("pid =="), "missing value for 'pid'");
+        assert_eq!(error("pid == &&"), "unexpected '&&'");
+        assert_eq!(error("pid == 1 &&"), "incomplete expression");
+        assert_eq!(error("pid == 1 pid"), "unexpected 'pid'");
+        assert_eq!(error("pid == 1 && foo == 2"), "unknown field 'foo'");
+        assert_eq!(error("pid == 1 && cpu 5"), "expected an operator after 'cpu'");
+        assert_eq!(error("pid == 1)"), "unexpected ')'");
+    }
+}
@@ -0,0 +1,100 @@
+// plain-text matching for the process search box
+use regex::{Regex, RegexBuilder};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SearchMode {
+    Substring,
+    Regex,
+    Fuzzy,
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Substring => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Fuzzy,
+            SearchMode::Fuzzy => SearchMode::Substring,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SearchMode::Substring => "substring",
+            SearchMode::Regex => "regex",
+            SearchMode::Fuzzy => "fuzzy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "substring" => Some(SearchMode::Substring),
+            "regex" => Some(SearchMode::Regex),
+            "fuzzy" => Some(SearchMode::Fuzzy),
+            _ => None,
+        }
+    }
+}
+
+// case-insensitive in every mode, like the original substring search
+#[derive(Debug)]
+pub struct Matcher {
+    mode: SearchMode,
+    pattern: Vec<char>,
+    regex: Option<Regex>,
+}
+
+impl Matcher {
+    pub fn new(text: &str, mode: SearchMode) -> Result<Self, String> {
+        let regex = match mode {
+            SearchMode::Regex => Some(
+                RegexBuilder::new(text)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|_| format!("bad regex /{}/", text))?,
+            ),
+            _ => None,
+        };
+        Ok(Self { mode, pattern: text.chars().map(lower).collect(), regex })
+    }
+
+    // char indices of the match in `haystack`, or None if it doesn't match
+    pub fn find(&self, haystack: &str) -> Option<Vec<usize>> {
+        let chars: Vec<char> = haystack.chars().map(lower).collect();
+        match self.mode {
+            SearchMode::Substring => {
+                if self.pattern.is_empty() {
+                    return Some(Vec::new());
+                }
+                let start = chars.windows(self.pattern.len()).position(|w| w == self.pattern.as_slice())?;
+                Some((start..start + self.pattern.len()).collect())
+            }
+            SearchMode::Regex => {
+                let found = self.regex.as_ref()?.find(haystack)?;
+                let start = haystack[..found.start()].chars().count();
+                Some((start..start + found.as_str().chars().count()).collect())
+            }
+            // every pattern character in order, each taking the earliest place it can
+            SearchMode::Fuzzy => {
+                let mut indices = Vec::with_capacity(self.pattern.len());
+                let mut from = 0;
+                for p in &self.pattern {
+                    if p.is_whitespace() {
+                        continue;
+                    }
+                    let i = from + chars[from..].iter().position(|c| c == p)?;
+                    indices.push(i);
+                    from = i + 1;
+                }
+                Some(indices)
+            }
+        }
+    }
+
+    pub fn is_match(&self, haystack: &str) -> bool {
+        self.find(haystack).is_some()
+    }
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
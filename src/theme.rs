@@ -0,0 +1,324 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use syntect::highlighting::{Color as ThemeColor, Theme, ThemeSet};
+use crate::config::{ColorMode, Config, UiColors};
+
+// below this a color pair is hard to read at all; WCAG's minimum for large text
+const UNREADABLE_CONTRAST: f64 = 3.0;
+// WCAG AA for normal text
+const LOW_CONTRAST: f64 = 4.5;
+
+// every theme xtop knows about, keyed by file stem like syntect's add_from_folder
+pub struct ThemeLibrary {
+    pub themes: ThemeSet,
+    // role colors that base16 and native themes set directly, applied before user overrides
+    pub presets: HashMap<String, Vec<(String, String)>>,
+    // every file found in the theme dir, with the name it loaded as, why it failed, or that it was skipped
+    pub files: Vec<(PathBuf, ThemeFile)>,
+}
+
+pub enum ThemeFile {
+    Loaded(String),
+    Failed(String),
+    // not a theme format xtop reads
+    Skipped,
+}
+
+impl ThemeLibrary {
+    pub fn load(dir: Option<&Path>) -> Self {
+        let mut library = Self {
+            themes: ThemeSet::load_defaults(),
+            presets: HashMap::new(),
+            files: Vec::new(),
+        };
+
+        if let Some(dir) = dir {
+            let mut files = Vec::new();
+            collect_files(dir, &mut files, &mut HashSet::new());
+            files.sort();
+            for path in files {
+                library.load_file(&path);
+            }
+        }
+        library
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.themes.themes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn get(&self, name: &str) -> Option<(&Theme, &[(String, String)])> {
+        let preset = self.presets.get(name).map(|p| p.as_slice()).unwrap_or(&[]);
+        self.themes.themes.get(name).map(|theme| (theme, preset))
+    }
+
+    fn load_file(&mut self, path: &Path) {
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else { return };
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+
+        let loaded = match extension.as_str() {
+            "tmtheme" => ThemeSet::get_theme(path)
+                .map(|theme| (theme, Vec::new()))
+                .map_err(|e| e.to_string()),
+            "yaml" | "yml" => fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_base16(&text, stem)),
+            "xtop" => fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_native(&text, stem)),
+            _ => {
+                self.files.push((path.to_path_buf(), ThemeFile::Skipped));
+                return;
+            }
+        };
+
+        match loaded {
+            Ok((theme, preset)) => {
+                if !preset.is_empty() {
+                    self.presets.insert(stem.to_string(), preset);
+                }
+                self.themes.themes.insert(stem.to_string(), theme);
+                self.files.push((path.to_path_buf(), ThemeFile::Loaded(stem.to_string())));
+            }
+            Err(e) => self.files.push((path.to_path_buf(), ThemeFile::Failed(e))),
+        }
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.files.iter().filter(|(_, file)| matches!(file, ThemeFile::Failed(_))).count()
+    }
+}
+
+// symlinked directories are followed, but each one only once, so a link back up the tree can't loop
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>, visited: &mut HashSet<PathBuf>) {
+    let Ok(canonical) = dir.canonicalize() else { return };
+    if !visited.insert(canonical) {
+        return;
+    }
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files, visited);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+// "#1d2021", "1d2021" or "#fff"
+fn parse_hex(value: &str) -> Option<ThemeColor> {
+    let hex = value.trim().trim_start_matches('#');
+    // checked first, so the slicing below stays on char boundaries
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return None,
+    };
+    let channel = |i: usize| u8::from_str_radix(&expanded[i..i + 2], 16).ok();
+    Some(ThemeColor { r: channel(0)?, g: channel(2)?, b: channel(4)?, a: 255 })
+}
+
+fn hex_string(c: ThemeColor) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+// a bare theme carrying only the background and foreground, so UiColors::from_theme
+// can derive the menu and selection shades the same way it does for tmThemes
+fn base_theme(name: &str, bg: ThemeColor, fg: ThemeColor) -> Theme {
+    let mut theme = Theme { name: Some(name.to_string()), ..Theme::default() };
+    theme.settings.background = Some(bg);
+    theme.settings.foreground = Some(fg);
+    theme
+}
+
+// base16 and base24 schemes, in both the classic flat layout (`scheme:` + `base00:`)
+// and the newer tinted-theming one (`name:` + a nested `palette:` map)
+fn parse_base16(text: &str, stem: &str) -> Result<(Theme, Vec<(String, String)>), String> {
+    let mut name = stem.to_string();
+    let mut palette: HashMap<String, ThemeColor> = HashMap::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let key = key.trim().to_lowercase();
+        // drop trailing comments and the quotes around values
+        let value = value.split(" #").next().unwrap_or("").trim().trim_matches(|c| c == '"' || c == '\'');
+
+        if key == "scheme" || key == "name" {
+            name = value.to_string();
+        } else if key.len() == 6 && key.starts_with("base") {
+            let color = parse_hex(value).ok_or_else(|| format!("{} is not a hex color: {}", key, value))?;
+            palette.insert(key, color);
+        }
+    }
+
+    let missing: Vec<String> = (0..16)
+        .map(|i| format!("base{:02x}", i))
+        .filter(|key| !palette.contains_key(key))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("not a base16 scheme, missing {}", missing.join(", ")));
+    }
+
+    let hex = |key: &str| hex_string(palette[key]);
+    // base24 adds brighter accents in base10 - base17
+    let bright = |key: &str, fallback: &str| palette.get(key).map(|c| hex_string(*c)).unwrap_or_else(|| hex(fallback));
+
+    // roles follow the base16 styling guidelines
+    let mut preset = vec![
+        ("menu_bg", hex("base01")),
+        ("selected_bg", hex("base02")),
+        ("pipe", hex("base03")),
+        ("accent", hex("base0d")),
+        ("title", hex("base0e")),
+        ("gauge_low", bright("base14", "base0b")),
+        ("gauge_high", bright("base12", "base08")),
+        ("search_text", hex("base07")),
+        ("error", hex("base08")),
+        ("mem_total", hex("base0a")),
+        ("mem_used", hex("base08")),
+        ("mem_avail", hex("base0b")),
+        ("mem_free", hex("base0c")),
+    ];
+    for role in ["border", "search_border", "popup_border", "header", "hot_key", "muted"] {
+        preset.push((role, hex("base0d")));
+    }
+
+    let theme = base_theme(&name, palette["base00"], palette["base05"]);
+    Ok((theme, preset.into_iter().map(|(k, v)| (k.to_string(), v)).collect()))
+}
+
+// xtop's own format: `<role>=<color>` lines using the same role names as
+// `color.<role>` in xtoprc; bg and fg are required, everything else is derived
+fn parse_native(text: &str, stem: &str) -> Result<(Theme, Vec<(String, String)>), String> {
+    let mut name = stem.to_string();
+    let mut bg = None;
+    let mut fg = None;
+    let mut preset = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("expected role=color, found: {}", line));
+        };
+        let (key, value) = (key.trim(), value.trim());
+        match key {
+            "name" => name = value.to_string(),
+            "bg" => bg = Some(parse_hex(value).ok_or_else(|| format!("bg is not a hex color: {}", value))?),
+            "fg" => fg = Some(parse_hex(value).ok_or_else(|| format!("fg is not a hex color: {}", value))?),
+            _ => preset.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    let (Some(bg), Some(fg)) = (bg, fg) else {
+        return Err(String::from("bg and fg are required"));
+    };
+    Ok((base_theme(&name, bg, fg), preset))
+}
+
+// `xtop --check-themes`: load every file in the theme dir and report what each one
+// looks like once xtop derives its colors. Returns false if anything needs attention.
+pub fn check_themes() -> bool {
+    let Some(dir) = Config::get_theme_dir() else {
+        println!("Could not find the home directory");
+        return false;
+    };
+    let library = ThemeLibrary::load(Some(&dir));
+    let settings = Config::load_config();
+
+    println!("Checking themes in {}", dir.display());
+    if !settings.color_overrides.is_empty() {
+        println!("(including {} color override(s) from xtoprc)", settings.color_overrides.len());
+    }
+    println!();
+
+    let mut unreadable = 0;
+    let mut skipped = 0;
+    for (path, result) in &library.files {
+        let file = path.strip_prefix(&dir).unwrap_or(path).display();
+        let name = match result {
+            ThemeFile::Loaded(name) => name,
+            ThemeFile::Failed(e) => {
+                println!("  FAILED  {}", file);
+                println!("          {}", e);
+                continue;
+            }
+            ThemeFile::Skipped => {
+                skipped += 1;
+                println!("  skipped {}", file);
+                println!("          unsupported extension (expected .tmTheme, .yaml, .yml or .xtop)");
+                continue;
+            }
+        };
+
+        let Some((theme, preset)) = library.get(name) else { continue };
+        let overrides = [preset, settings.color_overrides.as_slice()].concat();
+        let (colors, rejected) = UiColors::resolve(theme, &overrides, ColorMode::TrueColor);
+
+        let pairs = [
+            ("text", colors.fg, colors.bg, LOW_CONTRAST),
+            ("selected row", colors.fg, colors.selected_bg, LOW_CONTRAST),
+            ("menu", colors.fg, colors.menu_bg, LOW_CONTRAST),
+            ("accent", colors.accent, colors.bg, UNREADABLE_CONTRAST),
+            ("title", colors.title, colors.bg, UNREADABLE_CONTRAST),
+        ];
+        let mut problems = Vec::new();
+        let mut ratios = Vec::new();
+        for (label, fg, bg, wanted) in pairs {
+            let Some(ratio) = UiColors::contrast(fg, bg) else { continue };
+            ratios.push(format!("{} {:.1}:1", label, ratio));
+            if ratio < UNREADABLE_CONTRAST {
+                problems.push(format!("{} is unreadable", label));
+            } else if ratio < wanted {
+                problems.push(format!("{} has low contrast", label));
+            }
+        }
+        for entry in rejected {
+            problems.push(format!("invalid color {}", entry));
+        }
+
+        let is_unreadable = problems.iter().any(|p| p.contains("unreadable"));
+        if is_unreadable {
+            unreadable += 1;
+        }
+        let status = if is_unreadable { "BAD" } else if problems.is_empty() { "ok" } else { "warn" };
+
+        println!("  {:<6}  {} ({})", status, file, name);
+        let roles: Vec<String> = colors.roles().iter().map(|(role, c)| format!("{} {}", role, color_name(*c))).collect();
+        for line in roles.chunks(5) {
+            println!("          {}", line.join("  "));
+        }
+        println!("          contrast: {}", ratios.join(", "));
+        for problem in problems {
+            println!("          ! {}", problem);
+        }
+    }
+
+    let failed = library.failed_count();
+    println!();
+    println!(
+        "{} theme file(s), {} failed to load, {} unreadable, {} other file(s) skipped",
+        library.files.len() - skipped, failed, unreadable, skipped,
+    );
+    failed == 0 && unreadable == 0
+}
+
+fn color_name(color: ratatui::style::Color) -> String {
+    match color {
+        ratatui::style::Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use ratatui::widgets::TableState;
+use crate::procfs;
+
+pub struct ThreadRow {
+    pub tid: u32,
+    pub name: String,
+    pub state: char,
+    pub cpu_usage: f64,
+    // total CPU time in seconds
+    pub cpu_time: f64,
+}
+
+// the threads of one process, sampled on every refresh
+pub struct ThreadView {
+    pub pid: u32,
+    pub name: String,
+    pub rows: Vec<ThreadRow>,
+    pub table_state: TableState,
+    previous: HashMap<u32, u64>,
+    sampled_at: Instant,
+    ticks_per_sec: f64,
+}
+
+impl ThreadView {
+    pub fn new(pid: u32, name: String) -> std::io::Result<Self> {
+        let mut view = Self {
+            pid,
+            name,
+            rows: Vec::new(),
+            table_state: TableState::default(),
+            previous: HashMap::new(),
+            sampled_at: Instant::now(),
+            ticks_per_sec: procfs::clock_ticks() as f64,
+        };
+        view.refresh()?;
+        view.table_state.select(Some(0));
+        Ok(view)
+    }
+
+    // fails once the process is gone
+    pub fn refresh(&mut self) -> std::io::Result<()> {
+        let tasks = procfs::tasks(self.pid)?;
+        let elapsed = self.sampled_at.elapsed().as_secs_f64();
+        self.sampled_at = Instant::now();
+
+        let selected_tid = self.table_state.selected().and_then(|i| self.rows.get(i)).map(|r| r.tid);
+
+        self.rows = tasks
+            .iter()
+            .map(|task| {
+                let cpu_usage = match self.previous.get(&task.tid) {
+                    Some(before) if elapsed > 0.0 => {
+                        task.ticks.saturating_sub(*before) as f64 / self.ticks_per_sec / elapsed * 100.0
+                    }
+                    _ => 0.0,
+                };
+                ThreadRow {
+                    tid: task.tid,
+                    name: task.name.clone(),
+                    state: task.state,
+                    cpu_usage,
+                    cpu_time: task.ticks as f64 / self.ticks_per_sec,
+                }
+            })
+            .collect();
+        self.rows.sort_by(|a, b| b.cpu_usage.total_cmp(&a.cpu_usage).then(a.tid.cmp(&b.tid)));
+        self.previous = tasks.iter().map(|t| (t.tid, t.ticks)).collect();
+
+        // keep the cursor on the same thread as it moves around
+        let index = selected_tid
+            .and_then(|tid| self.rows.iter().position(|r| r.tid == tid))
+            .or(self.table_state.selected().map(|i| i.min(self.rows.len().saturating_sub(1))));
+        self.table_state.select(index);
+        Ok(())
+    }
+
+    pub fn move_cursor(&mut self, down: bool) {
+        let count = self.rows.len();
+        if count == 0 { return; }
+        let i = match self.table_state.selected() {
+            Some(i) if down => (i + 1).min(count - 1),
+            Some(i) => i.saturating_sub(1),
+            None => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+}
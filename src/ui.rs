@@ -2,13 +2,21 @@ use default_net::get_default_interface;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     prelude::{Line, Style, Stylize},
-    style::Color,
     symbols,
     text::Span,
-    widgets::{Block, BorderType, Borders, Cell, Clear, LineGauge, Paragraph, Row, Table},
+    widgets::{Block, BorderType, Borders, Cell, Clear, LineGauge, Paragraph, Row, Table, TableState, Tabs},
     Frame,
 };
-use sysinfo::{System, Users};
+use sysinfo::System;
+use crate::priority::{NICE_MAX, NICE_MIN};
+use crate::app::{CgroupColumn, DiskColumns};
+use crate::details;
+use crate::help;
+use crate::connections::ConnectionSort;
+use crate::aggregate::{self, Aggregate, TableEntry};
+use crate::query::{self, Query};
+use crate::alerts::Panel;
+use crate::bulk::{self, Stage};
 
 pub fn ui(f: &mut Frame, app: &mut crate::App) {
     let colors = app.ui_colors;
@@ -16,41 +24,54 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
     // map colors
     let c_bg = colors.bg;
     let c_fg = colors.fg;
-    let c_border = colors.accent;
-
-    let c_pipe = match colors.menu_bg {
-        Color::Rgb(r, g, b) => {
-            if colors.is_dark {
-                Color::Rgb(r.saturating_add(40), g.saturating_add(40), b.saturating_add(40))
-            } else {
-                Color::Rgb(r.saturating_sub(40), g.saturating_sub(40), b.saturating_sub(40))
-            }
-        }
-        _ => Color::DarkGray, // fallback
-    };
-    let c_row_highlight = colors.selected_bg;
-    let c_border_search = colors.accent;
+    let c_border = colors.border;
+    let c_pipe = colors.pipe;
+    let c_border_search = colors.search_border;
     let c_title = colors.title;
-    let c_menu_mut = colors.accent;
-    let c_hot_key = colors.accent;
-    let c_table_header = colors.accent;
-    let c_popup_border = colors.accent;
+    let c_menu_mut = colors.muted;
+    let c_hot_key = colors.hot_key;
+    let c_table_header = colors.header;
+    let c_popup_border = colors.popup_border;
     let c_menu = colors.fg; // normal text
-    let c_mem_total = Color::Rgb(200, 200, 100);
-    let c_mem_used = Color::Rgb(200, 100, 100);
-    let c_mem_avail = Color::Rgb(100, 200, 100);
-    let c_mem_free = Color::Rgb(50, 255, 255);
+    let c_mem_total = colors.mem_total;
+    let c_mem_used = colors.mem_used;
+    let c_mem_avail = colors.mem_avail;
+    let c_mem_free = colors.mem_free;
 
     let mut process_list: Vec<_> = app.s.processes().values().collect();
-    if !app.filter_text.is_empty() {
-        process_list.retain(|p| {
-            p.pid().to_string().to_lowercase().contains(&app.filter_text.to_lowercase())
-                || p.name().to_string_lossy().to_lowercase().contains(&app.filter_text.to_lowercase())
-        });
+    // a query that doesn't parse leaves the list alone and marks the search box instead
+    let mut query_error = None;
+    let mut query = None;
+    if !app.search_input.is_empty() {
+        match Query::parse(app.search_input.text(), app.search_mode) {
+            Ok(q) => {
+                let ctx = query::Context {
+                    s: &app.s,
+                    users: &app.users,
+                    cgroups: &app.cgroups,
+                    last_cpus: &app.last_cpus,
+                    traffic: app.net_traffic.as_ref(),
+                    refresh_secs: app.refresh_secs,
+                    search_cmdline: app.search_cmdline,
+                };
+                process_list.retain(|p| q.matches(p, &ctx));
+                query = Some(q);
+            }
+            Err(e) => query_error = Some(e),
+        }
         if process_list.is_empty() {
             app.process_info = 0;
         }
     }
+    if let Some(label) = &app.group_filter {
+        process_list.retain(|p| app.cgroups.get(&p.pid()).is_some_and(|c| &c.label == label));
+    }
+    // pinned processes stay in the table whatever the search, but only the matches count as listed for tagging
+    let matched: std::collections::HashSet<_> = process_list.iter().map(|p| p.pid()).collect();
+    if !app.pins.is_empty() {
+        process_list.extend(app.s.processes().values().filter(|p| !matched.contains(&p.pid()) && app.pins.matches(p)));
+    }
+    let text_matcher = query.as_ref().and_then(|q| q.text_matcher());
 
     // setup terminal
     let size = f.area();
@@ -87,27 +108,43 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         .constraints([
             Constraint::Length(3 * if app.editing { 1 } else { 0 }),
             Constraint::Fill(1),
-            Constraint::Length((app.process_info as u16) * 7),
         ])
         .split(horizontal[1]);
 
     // search Box
+    let search_title = match (&app.history_search, &query_error) {
+        (Some(search), _) => {
+            let failing = if search.found.is_none() && !search.query.is_empty() { "failing " } else { "" };
+            Line::from(format!(" {}history search: {}_ ", failing, search.query)).style(Style::default().bold())
+        }
+        (None, Some(e)) => Line::from(format!(" {} ", e)).style(Style::default().fg(colors.error).bold()),
+        (None, None) => Line::from(" Type to search, escape to exit ").style(Style::default().bold()),
+    };
+    // scrolled so the cursor stays in view when the text is wider than the box
+    let (search_text, cursor_x) = app.search_input.visible(right_panel[0].width.saturating_sub(2) as usize);
     let search_style = match app.editing {
         false => Style::default(),
-        true => Style::default().fg(Color::White),
+        true => Style::default().fg(colors.search_text),
     };
-
-    let search_bar = Paragraph::new(app.filter_text.as_str())
-        .style(search_style)
+    let search_bar = Paragraph::new(Span::styled(search_text, search_style))
         .block(
             Block::default()
                 .title_style(c_title)
                 .borders(Borders::ALL)
-                .border_style(c_border_search)
+                .border_style(if query_error.is_some() { colors.error } else { c_border_search })
                 .border_type(BorderType::Rounded)
+                .title(search_title)
                 .title(
-                    Line::from(" Type to search, escape to exit ")
-                        .style(Style::default().bold()),
+                    Line::from(vec![
+                        Span::styled(" tab ", Style::default().fg(c_hot_key)),
+                        Span::styled(app.search_mode.name(), Style::default().fg(c_menu)),
+                        Span::styled(" ⇧tab ", Style::default().fg(c_hot_key)),
+                        Span::styled(
+                            if app.search_cmdline { "name+cmd " } else { "name " },
+                            Style::default().fg(c_menu),
+                        ),
+                    ])
+                        .right_aligned(),
                 ),
         )
         .bg(c_bg)
@@ -115,11 +152,8 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
     f.render_widget(search_bar, right_panel[0]);
 
     if app.editing {
-        let inner_width = right_panel[0].width.saturating_sub(2);
-        let cursor_x = (app.cursor_position as u16).min(inner_width);
-
         f.set_cursor_position((
-            right_panel[0].x + 1 + cursor_x,
+            right_panel[0].x + 1 + cursor_x as u16,
             right_panel[0].y + 1,
         ));
     }
@@ -157,7 +191,7 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
                 .title(Line::from(" Core Information ").style(Style::default().bold()))
                 .title_style(c_title)
                 .borders(Borders::ALL)
-                .border_style(c_border)
+                .border_style(alert_border(app, Panel::Cpu, c_border))
                 .border_type(BorderType::Rounded)
                 .title_bottom(
                     Line::from(vec![
@@ -184,11 +218,7 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
             let cpuusage = app.s.cpus().get(i - 1).unwrap().cpu_usage();
             let gauge = LineGauge::default()
                 .label("")
-                .filled_style(Style::new().fg(Color::Rgb(
-                    (cpuusage * 255.0 / 100.0) as u8,
-                    ((100.0 - cpuusage) * 255.0 / 100.0) as u8,
-                    0,
-                )))
+                .filled_style(Style::new().fg(colors.gauge(cpuusage / 100.0)))
                 .unfilled_style(Style::new().fg(colors.menu_bg))
                 .filled_symbol(symbols::line::THICK_HORIZONTAL)
                 .ratio((cpuusage / 100.0) as f64);
@@ -244,11 +274,12 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         ]),
     ];
 
-    let mut memory_vec = vec![];
-    memory_vec.push(app.s.total_memory() as f64);
-    memory_vec.push(app.s.used_memory() as f64);
-    memory_vec.push(app.s.available_memory() as f64);
-    memory_vec.push(app.s.free_memory() as f64);
+    let memory_vec = [
+        app.s.total_memory() as f64,
+        app.s.used_memory() as f64,
+        app.s.available_memory() as f64,
+        app.s.free_memory() as f64,
+    ];
 
     let mem_table = Table::new(mem_rows, [Constraint::Length(6), Constraint::Length(5)])
         .block(Block::default().borders(Borders::ALL))
@@ -258,7 +289,7 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
                 .title(Line::from(" Memory (GB) ").style(Style::default().bold()))
                 .title_style(c_title)
                 .borders(Borders::ALL)
-                .border_style(c_border)
+                .border_style(alert_border(app, Panel::Memory, c_border))
                 .border_type(BorderType::Rounded)
                 .bg(c_bg)
                 .fg(c_fg),
@@ -267,7 +298,7 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
     f.render_widget(mem_table, left_panel[1]);
 
     // memory gauge
-    let color_memory = vec![c_mem_total, c_mem_used, c_mem_avail, c_mem_free];
+    let color_memory = [c_mem_total, c_mem_used, c_mem_avail, c_mem_free];
     let mut area_vec = vec![];
     for i in 0..4 {
         area_vec.push(Rect::new(
@@ -372,7 +403,7 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
             app.current_col = 0;
         }
         1 => {
-            process_list.sort_by(|a, b| a.name().cmp(&b.name()));
+            process_list.sort_by(|a, b| a.name().cmp(b.name()));
             app.current_col = 1;
         }
         2 => {
@@ -383,6 +414,32 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
             process_list.sort_by(|a, b| a.cpu_usage().total_cmp(&b.cpu_usage()).reverse());
             app.current_col = 3;
         }
+        4 => {
+            if app.disk_columns == DiskColumns::Rates {
+                process_list.sort_by_key(|p| std::cmp::Reverse(p.disk_usage().read_bytes));
+            } else {
+                process_list.sort_by_key(|p| std::cmp::Reverse(p.disk_usage().total_read_bytes));
+            }
+            app.current_col = 4;
+        }
+        5 => {
+            if app.disk_columns == DiskColumns::Rates {
+                process_list.sort_by_key(|p| std::cmp::Reverse(p.disk_usage().written_bytes));
+            } else {
+                process_list.sort_by_key(|p| std::cmp::Reverse(p.disk_usage().total_written_bytes));
+            }
+            app.current_col = 5;
+        }
+        6 | 7 => {
+            if let Some(traffic) = &app.net_traffic {
+                let rate = |p: &&sysinfo::Process| {
+                    let (received, sent) = traffic.rate(p.pid().as_u32());
+                    if app.sort_col == 6 { received } else { sent }
+                };
+                process_list.sort_by(|a, b| rate(b).total_cmp(&rate(a)));
+            }
+            app.current_col = app.sort_col;
+        }
         _ => {}
     }
 
@@ -390,21 +447,201 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         process_list.reverse();
     }
 
+    // keep each group together, in the chosen order within it
+    if app.cgroup_column == CgroupColumn::Grouped {
+        process_list.sort_by(|a, b| {
+            let label = |p: &&sysinfo::Process| app.cgroups.get(&p.pid()).map(|c| c.label.as_str()).unwrap_or("");
+            label(a).cmp(label(b))
+        });
+    }
+
+    app.listed = process_list
+        .iter()
+        .filter(|p| matched.contains(&p.pid()))
+        .map(|p| (p.pid(), p.start_time()))
+        .collect();
+
+    // and go first, in the chosen order, outside any aggregate group
+    let (pinned, process_list): (Vec<_>, Vec<_>) = process_list.into_iter().partition(|p| app.pins.matches(p));
+    let mut entries: Vec<TableEntry> = pinned.into_iter().map(TableEntry::Process).collect();
+    entries.extend(aggregate::entries(
+        &process_list,
+        app.aggregate,
+        &app.s,
+        &app.users,
+        &app.expanded_groups,
+        app.current_col,
+        app.reverse,
+    ));
+
+    // processes that just exited stay a few refreshes, greyed out where they were, in the plain table
+    if app.aggregate == Aggregate::Off && app.search_input.is_empty() && app.group_filter.is_none() {
+        app.events.positions = entries.iter().enumerate().filter_map(|(i, e)| Some((e.pid()?, i))).collect();
+        let mut exited: Vec<_> = app.events.exited.iter().filter_map(|e| Some((e.position?, e))).collect();
+        exited.sort_by_key(|(i, _)| *i);
+        for (i, e) in exited {
+            entries.insert(i.min(entries.len()), TableEntry::Exited(e));
+        }
+    } else {
+        app.events.positions.clear();
+    }
+
+    // shown once the process table no longer borrows the process list
+    let mut notice: Option<String> = None;
+
+    // keep the cursor on the same process or group as the table re-sorts; a key that moved the cursor since the
+    // last frame moved it to the process that was on that row then
+    let mut followed = false;
+    if app.jump_to_pid.is_none() {
+        let moved = app.table_state.selected().filter(|i| Some(*i) != app.selected_row);
+        let found = match (moved, &app.selected_group, app.selected_pid) {
+            (Some(i), _, _) => app.row_pids.get(i).copied().flatten().and_then(|pid| entries.iter().position(|e| e.pid() == Some(pid))),
+            (None, Some(key), _) => entries.iter().position(|e| matches!(e, TableEntry::Group(group) if &group.key == key)),
+            (None, None, Some(pid)) => entries.iter().position(|e| e.pid() == Some(pid)),
+            (None, None, None) => None,
+        };
+        match found {
+            Some(i) => {
+                app.table_state.select(Some(i));
+                followed = moved.is_none();
+            }
+            None if moved.is_none() => {
+                if let Some(pid) = app.selected_pid.filter(|pid| app.s.process(*pid).is_none()) {
+                    let name = app.events.exited.iter().find(|e| e.pid == pid).map(|e| e.name.as_str()).unwrap_or("Process");
+                    notice = Some(format!("{} ({}) exited", name, pid));
+                    // onto its greyed-out row, if the table shows one
+                    if let Some(i) = entries.iter().position(|e| matches!(e, TableEntry::Exited(exited) if exited.pid == pid)) {
+                        app.table_state.select(Some(i));
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    if let Some(pid) = app.jump_to_pid {
+        match entries.iter().position(|e| e.pid() == Some(pid)) {
+            Some(i) => {
+                app.table_state.select(Some(i));
+                app.jump_to_pid = None;
+            }
+            // inside a collapsed group: open it and try again next frame
+            None if process_list.iter().any(|p| p.pid() == pid) => {
+                if let Some(process) = app.s.process(pid) {
+                    let key = aggregate::group_key(process, app.aggregate, &app.s, &app.users);
+                    app.expanded_groups.insert(key);
+                }
+            }
+            // hidden by the search or container filter: drop them and try again next frame
+            None if !app.search_input.is_empty() || app.group_filter.is_some() => {
+                app.search_input.clear();
+                app.group_filter = None;
+                app.editing = false;
+            }
+            None => {
+                app.jump_to_pid = None;
+                notice = Some(match app.s.process(pid) {
+                    Some(_) => format!("Process {} isn't shown in the table", pid),
+                    None => format!("Process {} is gone", pid),
+                });
+            }
+        }
+    }
+
+    // the list may have shrunk since the last frame
+    if app.table_state.selected().is_some_and(|i| i >= entries.len()) {
+        app.table_state.select(Some(entries.len().saturating_sub(1)));
+    }
+    let selected_entry = app.table_state.selected().and_then(|i| entries.get(i));
+    app.selected_pid = selected_entry.and_then(|e| e.pid());
+    app.selected_row = app.table_state.selected();
+    app.row_pids = entries.iter().map(|e| e.pid()).collect();
+    app.selected_group = match selected_entry {
+        Some(TableEntry::Group(group)) => Some(group.key.clone()),
+        _ => None,
+    };
+
     let uptime_secs: u64 = System::uptime();
     let d = uptime_secs / 86400;
     let h = (uptime_secs / 3600) % 24;
     let m = (uptime_secs / 60) % 60;
     let s = uptime_secs % 60;
 
-    let proc_rows: Vec<Row> = process_list
-        .iter()
-        .map(|p| {
-            Row::new(vec![
+    let match_style = Style::default().fg(c_hot_key).bold();
+    let process_row = |p: &sysinfo::Process, member: bool| {
+            // searching the command line shows it in place of the name
+            let name = if app.search_cmdline { query::command_line(p) } else { p.name().to_string_lossy().to_string() };
+            let mut name_line = match text_matcher.and_then(|m| m.find(&name)) {
+                Some(indices) => highlight_matches(&name, &indices, match_style),
+                None => Line::from(name),
+            };
+            if member {
+                name_line.spans.insert(0, Span::raw("  └ "));
+            } else if app.pins.matches(p) {
+                name_line.spans.insert(0, Span::styled("★ ", Style::default().fg(colors.accent)));
+            }
+            let tagged = app.tagged.contains(&(p.pid(), p.start_time()));
+            if tagged {
+                name_line.spans.insert(0, Span::raw("• "));
+            }
+            let mut cells = vec![
                 Cell::from(Line::from(p.pid().to_string()).right_aligned()),
-                Cell::from(p.name().to_string_lossy().to_string()),
+                Cell::from(name_line),
                 Cell::from(Line::from(format!("{:.1} MB", p.memory() as f64 / 1_048_576.0)).right_aligned()),
                 Cell::from(Line::from(format!("{:.1}%", p.cpu_usage())).right_aligned()),
+            ];
+            let disk = p.disk_usage();
+            match app.disk_columns {
+                DiskColumns::Rates => {
+                    cells.push(Cell::from(Line::from(human_bytes(disk.read_bytes as f64 / app.refresh_secs)).right_aligned()));
+                    cells.push(Cell::from(Line::from(human_bytes(disk.written_bytes as f64 / app.refresh_secs)).right_aligned()));
+                }
+                DiskColumns::Totals => {
+                    cells.push(Cell::from(Line::from(human_bytes(disk.total_read_bytes as f64)).right_aligned()));
+                    cells.push(Cell::from(Line::from(human_bytes(disk.total_written_bytes as f64)).right_aligned()));
+                }
+                DiskColumns::Off => {}
+            }
+            if let Some(traffic) = &app.net_traffic {
+                let (received, sent) = traffic.rate(p.pid().as_u32());
+                cells.push(Cell::from(Line::from(human_bytes(received)).right_aligned()));
+                cells.push(Cell::from(Line::from(human_bytes(sent)).right_aligned()));
+            }
+            if app.cgroup_column != CgroupColumn::Off {
+                let label = app.cgroups.get(&p.pid()).map(|c| c.label.clone()).unwrap_or_default();
+                cells.push(Cell::from(label));
+            }
+            if app.show_last_cpu {
+                let last_cpu = app.last_cpus.get(&p.pid()).map(|c| c.to_string()).unwrap_or_default();
+                cells.push(Cell::from(Line::from(last_cpu).right_aligned()));
+            }
+            if tagged {
+                Row::new(cells).style(Style::default().fg(colors.hot_key).bold())
+            } else if app.events.is_new(p.pid()) {
+                // just started
+                Row::new(cells).style(Style::default().fg(colors.accent).bold())
+            } else {
+                Row::new(cells)
+            }
+        };
+    let proc_rows: Vec<Row> = entries
+        .iter()
+        .map(|entry| match entry {
+            TableEntry::Process(p) => process_row(p, false),
+            TableEntry::Member(p) => process_row(p, true),
+            TableEntry::Group(group) => Row::new(vec![
+                Cell::from(Line::from(Span::styled(format!("×{}", group.members.len()), Style::default().fg(colors.muted))).right_aligned()),
+                Cell::from(format!("{} {}", if group.expanded { "▾" } else { "▸" }, group.key)),
+                Cell::from(Line::from(format!("{:.1} MB", group.memory as f64 / 1_048_576.0)).right_aligned()),
+                Cell::from(Line::from(format!("{:.1}%", group.cpu_usage)).right_aligned()),
+            ])
+                .style(Style::default().bold()),
+            TableEntry::Exited(e) => Row::new(vec![
+                Cell::from(Line::from(e.pid.to_string()).right_aligned()),
+                Cell::from(e.name.clone()),
+                Cell::from(Line::from("exited").right_aligned()),
             ])
+                .style(Style::default().fg(colors.muted)),
         })
         .collect();
 
@@ -414,49 +651,122 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         srow = 0;
     }
     if srow < nrows {
-        srow = srow + 1;
+        srow += 1;
     }
 
-    let proc_table = Table::new(
-        proc_rows,
-        [
-            Constraint::Length(7),
-            Constraint::Min(12),
-            Constraint::Length(10),
-            Constraint::Length(6),
-        ],
-    )
-        .header(Row::new(vec![
+    let mut proc_widths = vec![
+        Constraint::Length(7),
+        Constraint::Min(12),
+        Constraint::Length(10),
+        Constraint::Length(6),
+    ];
+    let mut proc_header = vec![
+        Line::from(vec![
+            Span::styled("p", Style::default().fg(c_hot_key)),
+            Span::styled("id", Style::default().fg(c_menu)),
+        ])
+            .right_aligned()
+            .style(Style::default().bold()),
+        Line::from(vec![
+            Span::styled("n", Style::default().fg(c_hot_key)),
+            Span::styled("ame", Style::default().fg(c_menu)),
+        ])
+            .left_aligned()
+            .style(Style::default().bold()),
+        Line::from(vec![
+            Span::styled("m", Style::default().fg(c_hot_key)),
+            Span::styled("emory", Style::default().fg(c_menu)),
+        ])
+            .right_aligned()
+            .style(Style::default().bold()),
+        Line::from(vec![
+            Span::styled("c", Style::default().fg(c_hot_key)),
+            Span::styled("pu", Style::default().fg(c_menu)),
+        ])
+            .right_aligned()
+            .style(Style::default().bold()),
+    ];
+    if app.disk_columns != DiskColumns::Off {
+        let (read, write) = match app.disk_columns {
+            DiskColumns::Rates => (("rea", "/s"), "rite/s"),
+            _ => (("rea", ""), "ritten"),
+        };
+        proc_widths.push(Constraint::Length(9));
+        proc_widths.push(Constraint::Length(9));
+        proc_header.push(
             Line::from(vec![
-                Span::styled("p", Style::default().fg(c_hot_key)),
-                Span::styled("id", Style::default().fg(c_menu)),
+                Span::styled(read.0, Style::default().fg(c_menu)),
+                Span::styled("d", Style::default().fg(c_hot_key)),
+                Span::styled(read.1, Style::default().fg(c_menu)),
             ])
                 .right_aligned()
                 .style(Style::default().bold()),
+        );
+        proc_header.push(
             Line::from(vec![
-                Span::styled("n", Style::default().fg(c_hot_key)),
-                Span::styled("ame", Style::default().fg(c_menu)),
+                Span::styled("w", Style::default().fg(c_hot_key)),
+                Span::styled(write, Style::default().fg(c_menu)),
             ])
-                .left_aligned()
+                .right_aligned()
                 .style(Style::default().bold()),
+        );
+    }
+    // the rates come from TCP socket counters; the kernel keeps none per UDP socket
+    if app.net_traffic.is_some() {
+        proc_widths.push(Constraint::Length(11));
+        proc_widths.push(Constraint::Length(11));
+        proc_header.push(
             Line::from(vec![
-                Span::styled("m", Style::default().fg(c_hot_key)),
-                Span::styled("emory", Style::default().fg(c_menu)),
+                Span::styled("tcp rec", Style::default().fg(c_menu)),
+                Span::styled("v", Style::default().fg(c_hot_key)),
+                Span::styled("/s", Style::default().fg(c_menu)),
             ])
                 .right_aligned()
                 .style(Style::default().bold()),
+        );
+        proc_header.push(
             Line::from(vec![
-                Span::styled("c", Style::default().fg(c_hot_key)),
-                Span::styled("pu", Style::default().fg(c_menu)),
+                Span::styled("tcp s", Style::default().fg(c_menu)),
+                Span::styled("e", Style::default().fg(c_hot_key)),
+                Span::styled("nt/s", Style::default().fg(c_menu)),
             ])
                 .right_aligned()
                 .style(Style::default().bold()),
-        ]))
-        .row_highlight_style(Style::default().bg(c_row_highlight))
+        );
+    }
+    if app.cgroup_column != CgroupColumn::Off {
+        proc_widths.push(Constraint::Length(20));
+        proc_header.push(
+            Line::from(Span::styled("cgroup", Style::default().fg(c_menu)))
+                .left_aligned()
+                .style(Style::default().bold()),
+        );
+    }
+    if app.show_last_cpu {
+        proc_widths.push(Constraint::Length(4));
+        proc_header.push(
+            Line::from(Span::styled("core", Style::default().fg(c_menu)))
+                .right_aligned()
+                .style(Style::default().bold()),
+        );
+    }
+
+    let proc_table = Table::new(proc_rows, proc_widths)
+        .header(Row::new(proc_header))
+        .row_highlight_style(colors.highlight())
         .block(
             Block::default()
                 .title(
-                    Line::from(format!(" Processes [{}/{}] ", srow, nrows))
+                    Line::from(format!(
+                        " Processes{}{}{} [{}/{}{}{}] ",
+                        app.group_filter.as_ref().map(|label| format!(" in {}", label)).unwrap_or_default(),
+                        if app.editing || app.search_input.is_empty() { String::new() } else { format!(" matching {}", app.search_input.text()) },
+                        if app.aggregate == Aggregate::Off { String::new() } else { format!(" by {}", app.aggregate.name()) },
+                        srow,
+                        nrows,
+                        if app.tagged.is_empty() { String::new() } else { format!(", {} tagged", app.tagged.len()) },
+                        if app.follow { ", following" } else { "" },
+                    ))
                         .style(Style::default().bold())
                         .left_aligned(),
                 )
@@ -474,14 +784,298 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
                         .right_aligned(),
                 )
                 .borders(Borders::ALL)
-                .border_style(c_border)
+                .border_style(alert_border(app, Panel::Processes, c_border))
                 .border_type(BorderType::Rounded)
                 .title_style(c_title)
                 .bg(c_bg)
                 .fg(c_fg),
         );
 
-    f.render_stateful_widget(proc_table, right_panel[1], &mut app.table_state);
+    if app.process_info == 1 {
+        // the details view takes this space, drawn below
+    } else if let Some(view) = &mut app.thread_view {
+        let thread_rows: Vec<Row> = view
+            .rows
+            .iter()
+            .map(|t| {
+                Row::new(vec![
+                    Cell::from(Line::from(t.tid.to_string()).right_aligned()),
+                    Cell::from(t.name.clone()),
+                    Cell::from(thread_state(t.state)),
+                    Cell::from(Line::from(format!("{:.1}%", t.cpu_usage)).right_aligned()),
+                    Cell::from(Line::from(format!("{}:{:05.2}", (t.cpu_time / 60.0) as u64, t.cpu_time % 60.0)).right_aligned()),
+                ])
+            })
+            .collect();
+
+        let header_style = Style::default().fg(c_menu).bold();
+        let nthreads = thread_rows.len();
+        let sthread = view.table_state.selected().map(|i| i + 1).unwrap_or(0).min(nthreads);
+        let thread_table = Table::new(
+            thread_rows,
+            [
+                Constraint::Length(7),
+                Constraint::Min(12),
+                Constraint::Length(8),
+                Constraint::Length(6),
+                Constraint::Length(10),
+            ],
+        )
+            .header(Row::new(vec![
+                Line::from("tid").right_aligned(),
+                Line::from("thread"),
+                Line::from("state"),
+                Line::from("cpu").right_aligned(),
+                Line::from("time").right_aligned(),
+            ]).style(header_style))
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(
+                        Line::from(format!(" Threads of {} ({}) [{}/{}] ", view.name, view.pid, sthread, nthreads))
+                            .style(Style::default().bold())
+                            .left_aligned(),
+                    )
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Back to processes ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_style(c_border)
+                    .border_type(BorderType::Rounded)
+                    .title_style(c_title)
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_stateful_widget(thread_table, right_panel[1], &mut view.table_state);
+    } else if let Some(view) = &mut app.connection_view {
+        let connection_rows: Vec<Row> = view
+            .rows
+            .iter()
+            .map(|c| {
+                Row::new(vec![
+                    Cell::from(c.socket.protocol.to_string()),
+                    Cell::from(c.socket.local.to_string()),
+                    Cell::from(c.socket.remote.to_string()),
+                    Cell::from(c.socket.state),
+                    Cell::from(Line::from(c.pid.map(|p| p.to_string()).unwrap_or_default()).right_aligned()),
+                    Cell::from(c.name.clone()),
+                ])
+            })
+            .collect();
+
+        // column headers with their sort key picked out, the sorted one underlined
+        let sort_header = |before: &'static str, key: &'static str, after: &'static str, sort: ConnectionSort| {
+            let style = if view.sort == sort { Style::default().bold().underlined() } else { Style::default().bold() };
+            Line::from(vec![
+                Span::styled(before, Style::default().fg(c_menu)),
+                Span::styled(key, Style::default().fg(c_hot_key)),
+                Span::styled(after, Style::default().fg(c_menu)),
+            ])
+                .style(style)
+        };
+        let nconnections = connection_rows.len();
+        let sconnection = view.table_state.selected().map(|i| i + 1).unwrap_or(0).min(nconnections);
+        let connection_table = Table::new(
+            connection_rows,
+            [
+                Constraint::Length(5),
+                Constraint::Min(15),
+                Constraint::Min(15),
+                Constraint::Length(11),
+                Constraint::Length(7),
+                Constraint::Min(8),
+            ],
+        )
+            .header(Row::new(vec![
+                sort_header("", "t", "ype", ConnectionSort::Protocol),
+                sort_header("l", "o", "cal", ConnectionSort::Local),
+                sort_header("", "r", "emote", ConnectionSort::Remote),
+                sort_header("", "s", "tate", ConnectionSort::State),
+                sort_header("", "p", "id", ConnectionSort::Pid).right_aligned(),
+                sort_header("", "n", "ame", ConnectionSort::Name),
+            ]))
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(
+                        Line::from(format!(" Connections [{}/{}] ", sconnection, nconnections))
+                            .style(Style::default().bold())
+                            .left_aligned(),
+                    )
+                    .title(
+                        Line::from(if view.filter_editing || !view.filter.is_empty() {
+                            vec![
+                                Span::styled(" Filter: ", Style::default().fg(c_menu)),
+                                Span::styled(view.filter.clone(), Style::default().fg(c_fg)),
+                                Span::styled(if view.filter_editing { "_ " } else { " " }, Style::default().fg(c_hot_key)),
+                            ]
+                        } else {
+                            Vec::new()
+                        })
+                        .right_aligned(),
+                    )
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Go to process ", Style::default().fg(c_menu)),
+                        Span::styled("/ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Filter ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Back to processes ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_style(c_border)
+                    .border_type(BorderType::Rounded)
+                    .title_style(c_title)
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_stateful_widget(connection_table, right_panel[1], &mut view.table_state);
+    } else if let Some(view) = &mut app.group_view {
+        let group_rows: Vec<Row> = view
+            .rows
+            .iter()
+            .map(|g| {
+                let style = if app.group_filter.as_ref() == Some(&g.label) { Style::default().bold() } else { Style::default() };
+                Row::new(vec![
+                    Cell::from(g.label.clone()),
+                    Cell::from(Line::from(g.processes.to_string()).right_aligned()),
+                    Cell::from(Line::from(format!("{:.1}%", g.cpu_usage)).right_aligned()),
+                    Cell::from(Line::from(format!("{:.1} MB", g.memory as f64 / 1_048_576.0)).right_aligned()),
+                    Cell::from(Span::styled(g.path.clone(), Style::default().fg(colors.muted))),
+                ])
+                    .style(style)
+            })
+            .collect();
+
+        let header_style = Style::default().fg(c_menu).bold();
+        let ngroups = group_rows.len();
+        let sgroup = view.table_state.selected().map(|i| i + 1).unwrap_or(0).min(ngroups);
+        let group_table = Table::new(
+            group_rows,
+            [
+                Constraint::Min(16),
+                Constraint::Length(5),
+                Constraint::Length(7),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+            .header(Row::new(vec![
+                Line::from("container / unit"),
+                Line::from("procs").right_aligned(),
+                Line::from("cpu").right_aligned(),
+                Line::from("memory").right_aligned(),
+                Line::from("cgroup"),
+            ]).style(header_style))
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(
+                        Line::from(format!(" Containers & Units [{}/{}] ", sgroup, ngroups))
+                            .style(Style::default().bold())
+                            .left_aligned(),
+                    )
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Show only these processes ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Back to processes ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_style(c_border)
+                    .border_type(BorderType::Rounded)
+                    .title_style(c_title)
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_stateful_widget(group_table, right_panel[1], &mut view.table_state);
+    } else if let Some(view) = &mut app.event_view {
+        let event_rows: Vec<Row> = app
+            .events
+            .events
+            .iter()
+            .rev()
+            .map(|e| {
+                let (kind, style) = if e.started {
+                    ("start", Style::default().fg(colors.accent))
+                } else {
+                    ("exit", Style::default().fg(colors.muted))
+                };
+                let lifetime = e.lifetime.map(|secs| {
+                    let (h, m, s) = s_to_hms(secs);
+                    format!("{}:{:02}:{:02}", h, m, s)
+                });
+                Row::new(vec![
+                    Cell::from(details::format_timestamp(e.time)),
+                    Cell::from(Span::styled(kind, style)),
+                    Cell::from(Line::from(e.pid.to_string()).right_aligned()),
+                    Cell::from(e.name.clone()),
+                    Cell::from(e.user.clone()),
+                    Cell::from(Line::from(lifetime.unwrap_or_default()).right_aligned()),
+                ])
+            })
+            .collect();
+
+        let header_style = Style::default().fg(c_menu).bold();
+        let nevents = event_rows.len();
+        let sevent = view.table_state.selected().map(|i| i + 1).unwrap_or(0).min(nevents);
+        let event_table = Table::new(
+            event_rows,
+            [
+                Constraint::Length(19),
+                Constraint::Length(5),
+                Constraint::Length(7),
+                Constraint::Fill(1),
+                Constraint::Length(12),
+                Constraint::Length(10),
+            ],
+        )
+            .header(Row::new(vec![
+                Line::from("time"),
+                Line::from("event"),
+                Line::from("pid").right_aligned(),
+                Line::from("name"),
+                Line::from("user"),
+                Line::from("ran for").right_aligned(),
+            ]).style(header_style))
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(
+                        Line::from(format!(" Process Events [{}/{}] ", sevent, nevents))
+                            .style(Style::default().bold())
+                            .left_aligned(),
+                    )
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Go to process ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Back to processes ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_style(c_border)
+                    .border_type(BorderType::Rounded)
+                    .title_style(c_title)
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_stateful_widget(event_table, right_panel[1], &mut view.table_state);
+    } else {
+        // without follow the table doesn't scroll after a process that moved out of view; the cursor just isn't shown
+        let visible = right_panel[1].height.saturating_sub(3) as usize;
+        let offset = app.table_state.offset();
+        let out_of_view = app.table_state.selected().is_some_and(|i| i < offset || i >= offset + visible);
+        if followed && !app.follow && out_of_view {
+            f.render_stateful_widget(proc_table, right_panel[1], &mut TableState::default().with_offset(offset));
+        } else {
+            f.render_stateful_widget(proc_table, right_panel[1], &mut app.table_state);
+        }
+    }
 
 
     ////////////////////////////////////////////////////////////////////////////////////////
@@ -492,67 +1086,146 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         app.process_info = 0;
     }
 
-    if app.table_state.selected().is_some() && app.process_info == 1 {
-        let users = Users::new_with_refreshed_list();
-
-        let selected_process = app.table_state.selected().unwrap();
-        let (hours, minutes, seconds) = s_to_hms(process_list[selected_process].run_time());
-
-        let pid = process_list[selected_process].pid();
-        let user_name = process_list[selected_process]
-            .user_id()
-            .and_then(|uid| users.get_user_by_id(uid))
-            .map(|user| user.name())
-            .unwrap_or("Unknown");
-
-        let path = match process_list[selected_process].exe() {
-            Some(p) => p.to_str().unwrap_or("Unknown"),
-            None => "Unknown",
+    if app.process_info == 1 {
+        let key_style = Style::default().fg(c_table_header);
+        let pairs = |pairs: Vec<(String, String)>| -> Vec<Line<'static>> {
+            // long keys (environment variables mostly) just push their value along
+            let width = pairs.iter().map(|(k, _)| k.chars().count()).max().unwrap_or(0).min(24) + 2;
+            pairs
+                .into_iter()
+                .map(|(k, v)| Line::from(vec![Span::styled(format!("{:<width$}", k), key_style), Span::raw(v)]))
+                .collect()
         };
 
-        let selected_processes_rows = vec![
-            Row::new(vec![Cell::from("PID: "), Cell::from(pid.to_string())]),
-            Row::new(vec![Cell::from("User Name: "), Cell::from(user_name)]),
-            Row::new(vec![Cell::from("Path: "), Cell::from(path)]),
-            Row::new(vec![
-                Cell::from("Command: "),
-                Cell::from(format!("{:?}", process_list[selected_process].cmd())),
-            ]),
-            Row::new(vec![
-                Cell::from("Run Time: "),
-                Cell::from(format!("{:?}:{:02}:{:02}", hours, minutes, seconds)),
-            ]),
-        ];
+        let content = app.selected_pid.and_then(|pid| app.s.process(pid)).map(|process| {
+            let title = format!(" Process Details: {} ({}) ", process.name().to_string_lossy(), process.pid());
+            let lines: Vec<Line> = match app.details_tab {
+                0 => pairs(details::overview(process, &app.s, &app.users, app.refresh_secs)),
+                1 => pairs(details::environment(process)),
+                2 => vec![Line::from(details::command(process))],
+                3 => pairs(details::memory(process)),
+                4 => match details::files(process) {
+                    Ok(files) => {
+                        let width = files.iter().map(|[fd, _, _]| fd.len()).max().unwrap_or(0).max(2) + 2;
+                        std::iter::once(Line::styled(format!("{:<width$}{:<8}{}", "FD", "Type", "Name"), key_style))
+                            .chain(files.into_iter().map(|[fd, kind, name]| {
+                                Line::from(vec![
+                                    Span::styled(format!("{:<width$}", fd), key_style),
+                                    Span::raw(format!("{:<8}{}", kind, name)),
+                                ])
+                            }))
+                            .collect()
+                    }
+                    Err(e) => vec![Line::from(format!("Open files unavailable: {}", e))],
+                },
+                _ => match details::limits(process) {
+                    Ok(limits) => std::iter::once(Line::styled(
+                        format!("{:<26}{:<12}{:<12}{}", "Limit", "Soft", "Hard", "Units"),
+                        key_style,
+                    ))
+                        .chain(limits.into_iter().map(|[name, soft, hard, units]| {
+                            Line::from(format!("{:<26}{:<12}{:<12}{}", name, soft, hard, units))
+                        }))
+                        .collect(),
+                    Err(e) => vec![Line::from(format!("Limits unavailable: {}", e))],
+                },
+            };
+            // the Files and Limits tabs start with a header row that should survive filtering
+            let header = matches!(app.details_tab, 4 | 5) && lines.len() > 1;
+            let filter = app.details_filter.to_lowercase();
+            let lines: Vec<Line> = if filter.is_empty() || app.details_tab == 2 {
+                lines
+            } else {
+                lines
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(i, line)| {
+                        (header && *i == 0) || line.to_string().to_lowercase().contains(&filter)
+                    })
+                    .map(|(_, line)| line)
+                    .collect()
+            };
+            (title, lines)
+        });
 
-        let selected_process_table = Table::new(
-            selected_processes_rows,
-            [Constraint::Fill(1), Constraint::Fill(3)],
-        )
-            .row_highlight_style(Style::default().bg(Color::Rgb(100, 100, 50))) // Visual cue for selection
-            .block(
-                Block::default()
-                    .title(Line::from(" Process Details ").style(Style::default().bold()))
+        match content {
+            Some((title, lines)) => {
+                let block = Block::default()
+                    .title(Line::from(title).style(Style::default().bold()))
+                    .title(
+                        Line::from(if app.details_filter_editing || !app.details_filter.is_empty() {
+                            vec![
+                                Span::styled(" Filter: ", Style::default().fg(c_menu)),
+                                Span::styled(app.details_filter.clone(), Style::default().fg(c_fg)),
+                                Span::styled(if app.details_filter_editing { "_ " } else { " " }, Style::default().fg(c_hot_key)),
+                            ]
+                        } else {
+                            Vec::new()
+                        })
+                        .right_aligned(),
+                    )
                     .borders(Borders::ALL)
                     .border_style(c_border)
                     .border_type(BorderType::Rounded)
                     .title_style(c_title)
                     .title_bottom(Line::from(vec![
-                        Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled(" ←→ 1-6 ", Style::default().fg(c_hot_key)),
+                        Span::styled("Tab ", Style::default().fg(c_menu)),
+                        Span::styled("↑↓ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Scroll ", Style::default().fg(c_menu)),
+                        Span::styled("/ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Filter ", Style::default().fg(c_menu)),
+                        Span::styled("↵ ", Style::default().fg(c_hot_key)),
                         Span::styled("Close ", Style::default().fg(c_menu)),
                     ]))
                     .bg(c_bg)
-                    .fg(c_fg),
-            );
-
-        f.render_widget(selected_process_table, right_panel[2]);
+                    .fg(c_fg);
+                let inner = block.inner(right_panel[1]);
+                f.render_widget(block, right_panel[1]);
+
+                let details_layout = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Length(2), Constraint::Fill(1)])
+                    .split(inner);
+
+                let tabs = Tabs::new(details::TABS)
+                    .select(app.details_tab)
+                    .style(Style::default().fg(c_menu))
+                    .highlight_style(Style::default().fg(c_hot_key).bold().underlined())
+                    .divider(Span::styled("|", Style::default().fg(c_pipe)));
+                f.render_widget(tabs, details_layout[0]);
+
+                // scroll no further than the wrapped text needs, so the long command line can be read to the end
+                let paragraph = Paragraph::new(lines).wrap(ratatui::widgets::Wrap { trim: false });
+                let text_area = details_layout[1];
+                let max_scroll = paragraph.line_count(text_area.width).saturating_sub(text_area.height as usize);
+                app.details_scroll = app.details_scroll.min(max_scroll as u16);
+                f.render_widget(paragraph.scroll((app.details_scroll, 0)), text_area);
+            }
+            None => app.process_info = 0,
+        }
     }
 
 
-    // bottom menu
+    // bottom menu, with a hint for the bulk actions while processes are tagged
+    let mut right_menu_spans = Vec::new();
+    if !app.tagged.is_empty() {
+        right_menu_spans.push(Span::styled("x", Style::default().fg(c_hot_key)));
+        right_menu_spans.push(Span::styled(format!(" Act on {} tagged ", app.tagged.len()), Style::default().fg(c_menu)));
+        right_menu_spans.push(Span::styled("| ", Style::default().fg(c_pipe)));
+    }
+    right_menu_spans.extend([
+        Span::styled("↵", Style::default().fg(c_hot_key)), Span::styled(" Info ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
+        Span::styled("s", Style::default().fg(c_hot_key)), Span::styled("earch ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
+        Span::styled("t", Style::default().fg(c_hot_key)), Span::styled("heme ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
+        Span::styled("q", Style::default().fg(c_hot_key)), Span::styled("uit ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
+        Span::styled("?", Style::default().fg(c_hot_key)), Span::styled(" Help ", Style::default().fg(c_menu)),
+    ]);
+    let right_menu_line = Line::from(right_menu_spans);
     let menu_layout = Layout::default()
         .direction(Direction::Horizontal)
-        // Let the left side expand infinitely, pinning the right side to exactly 42 characters
-        .constraints([Constraint::Fill(1), Constraint::Length(42)])
+        // Let the left side expand infinitely, pinning the right side to the width of its hints
+        .constraints([Constraint::Fill(1), Constraint::Length(right_menu_line.width() as u16)])
         .split(main_layout[1]);
 
     let show_theme_name = match app.theme_changed_time {
@@ -560,6 +1233,9 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
         None => false,
     };
 
+    if let Some(msg) = notice {
+        app.show_notification(msg);
+    }
     let show_notification = match app.notification_time {
         Some(time) => time.elapsed().as_secs() < 3,
         None => false,
@@ -587,29 +1263,37 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
     let left_menu = Paragraph::new(Line::from(left_menu_spans))
         .block(Block::default().bg(colors.menu_bg));
 
-    let right_menu = Paragraph::new(Line::from(vec![
-        Span::styled("↵", Style::default().fg(c_hot_key)), Span::styled(" Info ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
-        Span::styled("s", Style::default().fg(c_hot_key)), Span::styled("earch ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
-        Span::styled("t", Style::default().fg(c_hot_key)), Span::styled("heme ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
-        Span::styled("q", Style::default().fg(c_hot_key)), Span::styled("uit ", Style::default().fg(c_menu)), Span::styled("| ", Style::default().fg(c_pipe)),
-        Span::styled("? ", Style::default().fg(c_hot_key)), Span::styled("", Style::default().fg(c_menu)),
-    ]))
+    let right_menu = Paragraph::new(right_menu_line)
         .alignment(ratatui::layout::Alignment::Right)
         .bg(colors.menu_bg);
 
     f.render_widget(left_menu, menu_layout[0]);
     f.render_widget(right_menu, menu_layout[1]);
 
-    // about popup
+    // help popup: every key, then where xtop lives
     if app.show_popup {
-        let area = centered_rect(f.area());
-        let help_text = vec![
-            Line::from(Span::styled(" https://github.com/mabognar ", colors.fg)),
-            Line::from(vec![Span::styled(
-                " https://crates.io/crates/xtop ",
-                colors.fg,
-            )]),
-        ];
+        let mut help_text: Vec<Line> = Vec::new();
+        for (key, action) in help::KEYS {
+            if key.is_empty() {
+                if !help_text.is_empty() {
+                    help_text.push(Line::from(""));
+                }
+                help_text.push(Line::from(Span::styled(format!(" {}", action), Style::default().fg(c_title).bold())));
+            } else {
+                help_text.push(Line::from(vec![
+                    Span::styled(format!(" {:<12} ", key), Style::default().fg(c_hot_key)),
+                    Span::styled(*action, Style::default().fg(colors.fg)),
+                ]));
+            }
+        }
+        help_text.push(Line::from(""));
+        help_text.push(Line::from(Span::styled(" https://github.com/mabognar ", colors.fg)));
+        help_text.push(Line::from(Span::styled(" https://crates.io/crates/xtop ", colors.fg)));
+
+        let height = (help_text.len() as u16 + 2).min(f.area().height.saturating_sub(2));
+        let area = centered_rect(f.area(), 64, height);
+        // the key list is longer than short terminals, so it scrolls; clamp here where the height is known
+        app.help_scroll = app.help_scroll.min((help_text.len() as u16).saturating_sub(height.saturating_sub(2)));
 
         const PKG_VERSION: &str = env!("CARGO_PKG_VERSION");
         let block = Block::default()
@@ -617,41 +1301,468 @@ pub fn ui(f: &mut Frame, app: &mut crate::App) {
                 Span::raw(" xtop "),
                 Span::raw(format!("({}) ", PKG_VERSION)),
             ]))
+            .title_style(c_title)
             .title_bottom(Line::from(vec![
-                Span::raw(" To close, type "),
+                Span::styled(" ↑↓ ", c_hot_key),
+                Span::styled("Scroll ", c_menu),
                 Span::styled("? ", c_hot_key),
+                Span::styled("Close ", c_menu),
             ]))
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
             .bg(c_bg);
 
-        let help_para = Paragraph::new(help_text)
-            .block(block)
-            .wrap(ratatui::widgets::Wrap { trim: true });
+        let help_para = Paragraph::new(help_text).block(block).scroll((app.help_scroll, 0));
 
         f.render_widget(Clear, area); // clear the area under popup
         f.render_widget(help_para, area);
     }
+
+    // priority popup
+    if let Some(edit) = &app.priority_edit {
+        let area = centered_rect(f.area(), 46, 7);
+        let field_style = |field: u8| {
+            if edit.field == field { colors.highlight() } else { Style::default() }
+        };
+        let level = match edit.io {
+            Some(io) if io.class.has_level() => io.level.to_string(),
+            Some(_) => String::from("-"),
+            None => String::new(),
+        };
+        let io_class = match edit.io {
+            Some(io) => io.class.name(),
+            None => "unavailable",
+        };
+
+        let priority_rows = vec![
+            Row::new(vec![
+                Cell::from(" Nice"),
+                Cell::from(Span::styled(format!(" {:>3} ", edit.nice_text), field_style(0))),
+                Cell::from(Span::styled(format!("({} to {})", NICE_MIN, NICE_MAX), Style::default().fg(c_menu_mut))),
+            ]),
+            Row::new(vec![
+                Cell::from(" I/O class"),
+                Cell::from(Span::styled(format!(" {} ", io_class), field_style(1))),
+                Cell::from(""),
+            ]),
+            Row::new(vec![
+                Cell::from(" I/O level"),
+                Cell::from(Span::styled(format!(" {:>3} ", level), field_style(2))),
+                Cell::from(Span::styled("(0 high to 7 low)", Style::default().fg(c_menu_mut))),
+            ]),
+        ];
+
+        let priority_table = Table::new(
+            priority_rows,
+            [Constraint::Length(11), Constraint::Length(13), Constraint::Fill(1)],
+        )
+            .block(
+                Block::default()
+                    .title(Line::from(format!(" Priority: {} ({}) ", edit.name, edit.pid)).style(Style::default().bold()))
+                    .title_style(c_title)
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" ± ", Style::default().fg(c_hot_key)),
+                        Span::styled("Step ", Style::default().fg(c_menu)),
+                        Span::styled("tab ", Style::default().fg(c_hot_key)),
+                        Span::styled("Field ", Style::default().fg(c_menu)),
+                        Span::styled("↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Apply ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Cancel ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                    .padding(ratatui::widgets::Padding::vertical(1))
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_widget(priority_table, area);
+    }
+
+    // affinity popup
+    if let Some(edit) = &mut app.affinity_edit {
+        let cpu_names: Vec<String> = edit.cpus.iter().map(|cpu| format!("cpu{}", cpu)).collect();
+        let cell_width = cpu_names.iter().map(|n| n.len()).max().unwrap_or(4) as u16 + 5;
+        let max_width = f.area().width.saturating_sub(8);
+        edit.columns = ((max_width.saturating_sub(2)) / cell_width).clamp(1, 8) as usize;
+        edit.columns = edit.columns.min(edit.allowed.len().max(1));
+        let grid_rows = edit.allowed.len().div_ceil(edit.columns);
+
+        let width = (edit.columns as u16 * cell_width + 2).max(44);
+        let height = (grid_rows as u16 + 4).min(f.area().height.saturating_sub(4));
+        let area = centered_rect(f.area(), width, height);
+
+        // scroll the grid to keep the cursor's row in view
+        let visible_rows = (height.saturating_sub(3) as usize).max(1);
+        let cursor_row = edit.cursor / edit.columns;
+        if cursor_row < edit.scroll {
+            edit.scroll = cursor_row;
+        } else if cursor_row >= edit.scroll + visible_rows {
+            edit.scroll = cursor_row + 1 - visible_rows;
+        }
+
+        let affinity_rows: Vec<Row> = edit
+            .allowed
+            .chunks(edit.columns)
+            .enumerate()
+            .skip(edit.scroll)
+            .map(|(row, chunk)| {
+                Row::new(chunk.iter().enumerate().map(|(col, allowed)| {
+                    let cell = row * edit.columns + col;
+                    let mark = if *allowed { "[x]" } else { "[ ]" };
+                    let style = if cell == edit.cursor { colors.highlight() } else { Style::default() };
+                    Cell::from(Span::styled(format!("{} {}", mark, cpu_names[cell]), style))
+                }).collect::<Vec<_>>())
+            })
+            .collect();
+
+        let allowed_count = edit.allowed.iter().filter(|a| **a).count();
+        let affinity_table = Table::new(affinity_rows, vec![Constraint::Length(cell_width); edit.columns])
+            .column_spacing(0)
+            .block(
+                Block::default()
+                    .title(Line::from(format!(" Affinity: {} ({}) [{}/{}] ", edit.name, edit.pid, allowed_count, edit.allowed.len())).style(Style::default().bold()))
+                    .title_style(c_title)
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" space ", Style::default().fg(c_hot_key)),
+                        Span::styled("Toggle ", Style::default().fg(c_menu)),
+                        Span::styled("a ", Style::default().fg(c_hot_key)),
+                        Span::styled("All ", Style::default().fg(c_menu)),
+                        Span::styled("↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Apply ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Cancel ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                    .padding(ratatui::widgets::Padding::new(1, 0, 1, 0))
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_widget(affinity_table, area);
+    }
+
+    // theme picker popup
+    if app.theme_picker {
+        let themes: Vec<String> = app.filtered_themes().into_iter().cloned().collect();
+        let height = (themes.len() as u16 + 4).min(f.area().height.saturating_sub(4)).max(6);
+        let area = centered_rect(f.area(), 40, height);
+
+        let theme_rows: Vec<Row> = themes
+            .iter()
+            .map(|t| Row::new(vec![Cell::from(format!(" {}", t))]))
+            .collect();
+
+        let theme_table = Table::new(theme_rows, [Constraint::Fill(1)])
+            .header(
+                Row::new(vec![Cell::from(Line::from(vec![
+                    Span::styled(" / ", Style::default().fg(c_hot_key)),
+                    Span::styled(app.theme_filter.clone(), Style::default().fg(c_menu)),
+                ]))])
+                    .bottom_margin(1),
+            )
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(Line::from(format!(" Themes [{}] ", themes.len())).style(Style::default().bold()))
+                    .title_style(c_title)
+                    .title_bottom(Line::from(vec![
+                        Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                        Span::styled("Apply ", Style::default().fg(c_menu)),
+                        Span::styled("esc ", Style::default().fg(c_hot_key)),
+                        Span::styled("Cancel ", Style::default().fg(c_menu)),
+                    ]))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(theme_table, area, &mut app.theme_picker_state);
+        f.set_cursor_position((area.x + 4 + app.theme_filter.chars().count() as u16, area.y + 1));
+    }
+    // saved views popup
+    if let Some(picker) = &mut app.view_picker {
+        let height = (app.views.len() as u16 + 4).min(f.area().height.saturating_sub(4)).max(6);
+        let area = centered_rect(f.area(), 60, height);
+
+        let view_rows: Vec<Row> = app
+            .views
+            .iter()
+            .enumerate()
+            .map(|(i, view)| {
+                let key = if i < 9 { format!(" {} ", i + 1) } else { String::from("   ") };
+                Row::new(vec![Cell::from(Line::from(vec![
+                    Span::styled(key, Style::default().fg(c_hot_key)),
+                    Span::raw(format!("{:<14} ", view.name)),
+                    Span::styled(view.describe(), Style::default().fg(colors.muted)),
+                ]))])
+            })
+            .collect();
+
+        let header = match &picker.naming {
+            Some(name) => Line::from(vec![
+                Span::styled(" Save as: ", Style::default().fg(c_hot_key)),
+                Span::styled(name.clone(), Style::default().fg(c_menu)),
+            ]),
+            None if app.views.is_empty() => Line::from(Span::styled(" No saved views yet", Style::default().fg(c_menu))),
+            None => Line::from(""),
+        };
+        let hints = if picker.naming.is_some() {
+            vec![
+                Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                Span::styled("Save ", Style::default().fg(c_menu)),
+                Span::styled("esc ", Style::default().fg(c_hot_key)),
+                Span::styled("Cancel ", Style::default().fg(c_menu)),
+            ]
+        } else {
+            vec![
+                Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                Span::styled("Apply ", Style::default().fg(c_menu)),
+                Span::styled("s ", Style::default().fg(c_hot_key)),
+                Span::styled("Save current ", Style::default().fg(c_menu)),
+                Span::styled("d ", Style::default().fg(c_hot_key)),
+                Span::styled("Delete ", Style::default().fg(c_menu)),
+                Span::styled("esc ", Style::default().fg(c_hot_key)),
+                Span::styled("Close ", Style::default().fg(c_menu)),
+            ]
+        };
+
+        let view_table = Table::new(view_rows, [Constraint::Fill(1)])
+            .header(Row::new(vec![Cell::from(header)]).bottom_margin(1))
+            .row_highlight_style(colors.highlight())
+            .block(
+                Block::default()
+                    .title(Line::from(format!(" Views [{}] ", app.views.len())).style(Style::default().bold()))
+                    .title_style(c_title)
+                    .title_bottom(Line::from(hints))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(view_table, area, &mut picker.table_state);
+        if let Some(name) = &picker.naming {
+            f.set_cursor_position((area.x + 11 + name.chars().count() as u16, area.y + 1));
+        }
+    }
+
+    // bulk action popup
+    if let Some(menu) = &mut app.bulk_menu {
+        let targets = if app.tagged.is_empty() {
+            app.selected_pid
+                .and_then(|pid| app.s.process(pid))
+                .map(|p| format!("{} ({})", p.name().to_string_lossy(), p.pid()))
+                .unwrap_or_default()
+        } else {
+            format!("{} tagged", app.tagged.len())
+        };
+        let rows: Vec<Row> = match &menu.stage {
+            Stage::Actions => bulk::ACTIONS
+                .iter()
+                .map(|(key, label)| {
+                    Row::new(vec![Cell::from(Line::from(vec![
+                        Span::styled(format!(" {} ", key), Style::default().fg(c_hot_key)),
+                        Span::raw(*label),
+                    ]))])
+                })
+                .collect(),
+            Stage::Signals => bulk::SIGNALS
+                .iter()
+                .map(|(_, name)| Row::new(vec![Cell::from(format!(" SIG{}", name))]))
+                .collect(),
+            Stage::Renice(text) => vec![Row::new(vec![Cell::from(Line::from(vec![
+                Span::styled(format!(" Nice ({}..{}): ", NICE_MIN, NICE_MAX), Style::default().fg(c_hot_key)),
+                Span::styled(text.clone(), Style::default().fg(c_menu)),
+            ]))])],
+            Stage::Confirm(index, count) => vec![Row::new(vec![Cell::from(Line::from(Span::styled(
+                format!(" Send SIG{} to {} processes?", bulk::SIGNALS[*index].1, count),
+                Style::default().fg(c_hot_key),
+            )))])],
+        };
+        let title = match menu.stage {
+            Stage::Actions => format!(" Actions: {} ", targets),
+            Stage::Signals => format!(" Signal: {} ", targets),
+            Stage::Renice(_) => format!(" Renice: {} ", targets),
+            Stage::Confirm(..) => format!(" Confirm: {} ", targets),
+        };
+        let hints = match menu.stage {
+            Stage::Actions => vec![
+                Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                Span::styled("Choose ", Style::default().fg(c_menu)),
+                Span::styled("esc ", Style::default().fg(c_hot_key)),
+                Span::styled("Close ", Style::default().fg(c_menu)),
+            ],
+            Stage::Signals | Stage::Renice(_) => vec![
+                Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+                Span::styled("Apply ", Style::default().fg(c_menu)),
+                Span::styled("esc ", Style::default().fg(c_hot_key)),
+                Span::styled("Back ", Style::default().fg(c_menu)),
+            ],
+            Stage::Confirm(..) => vec![
+                Span::styled(" y ", Style::default().fg(c_hot_key)),
+                Span::styled("Send ", Style::default().fg(c_menu)),
+                Span::styled("n ", Style::default().fg(c_hot_key)),
+                Span::styled("Back ", Style::default().fg(c_menu)),
+            ],
+        };
+        let height = rows.len() as u16 + 2;
+        let area = centered_rect(f.area(), 36, height);
+        let renice = if let Stage::Renice(text) = &menu.stage { Some(text.chars().count() as u16) } else { None };
+
+        let bulk_table = Table::new(rows, [Constraint::Fill(1)])
+            .row_highlight_style(if menu.is_empty() { Style::default() } else { colors.highlight() })
+            .block(
+                Block::default()
+                    .title(Line::from(title).style(Style::default().bold()))
+                    .title_style(c_title)
+                    .title_bottom(Line::from(hints))
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                    .bg(c_bg)
+                    .fg(c_fg),
+            );
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(bulk_table, area, &mut menu.table_state);
+        if let Some(len) = renice {
+            let label = format!(" Nice ({}..{}): ", NICE_MIN, NICE_MAX).chars().count() as u16;
+            f.set_cursor_position((area.x + 1 + label + len, area.y + 1));
+        }
+    }
+
+    // pin pattern prompt
+    if let Some(editor) = &mut app.pin_prompt {
+        let area = centered_rect(f.area(), 50, 3);
+        let label = " Name: ";
+        let width = area.width.saturating_sub(2 + label.len() as u16 + 1) as usize;
+        let (text, cursor) = editor.visible(width);
+        let hints = vec![
+            Span::styled(" ↵ ", Style::default().fg(c_hot_key)),
+            Span::styled("Pin/unpin ", Style::default().fg(c_menu)),
+            Span::styled("esc ", Style::default().fg(c_hot_key)),
+            Span::styled("Cancel ", Style::default().fg(c_menu)),
+        ];
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled(label, Style::default().fg(c_hot_key)),
+            Span::styled(text, Style::default().fg(c_menu)),
+        ]))
+        .block(
+            Block::default()
+                .title(Line::from(" Pin pattern (* any, ? one char) ").style(Style::default().bold()))
+                .title_style(c_title)
+                .title_bottom(Line::from(hints))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(c_popup_border).bg(colors.bg))
+                .bg(c_bg)
+                .fg(c_fg),
+        );
+
+        f.render_widget(Clear, area);
+        f.render_widget(prompt, area);
+        f.set_cursor_position((area.x + 1 + label.len() as u16 + cursor as u16, area.y + 1));
+    }
+}
+
+// a panel with an alert on blinks between its border color and the error color
+fn alert_border(app: &crate::App, panel: Panel, normal: ratatui::style::Color) -> ratatui::style::Color {
+    let now = std::time::Instant::now();
+    let blink = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .is_ok_and(|t| t.as_secs() % 2 == 0);
+    if blink && app.alerts.iter().any(|a| a.panel() == panel && a.is_on(now)) {
+        app.ui_colors.error
+    } else {
+        normal
+    }
 }
 
-fn centered_rect(r: Rect) -> Rect {
+fn centered_rect(r: Rect, width: u16, height: u16) -> Rect {
     let popup_layout = Layout::vertical([
         Constraint::Fill(1),
-        Constraint::Length(4),
+        Constraint::Length(height),
         Constraint::Fill(1),
     ])
         .split(r);
 
     Layout::horizontal([
         Constraint::Fill(1),
-        Constraint::Length(33),
+        Constraint::Length(width),
         Constraint::Fill(1),
     ])
         .split(popup_layout[1])[1]
 }
 
-fn s_to_hms(secs: u64) -> (u64, u64, u64) {
+// `text` with the chars at `indices` (sorted) picked out
+fn highlight_matches(text: &str, indices: &[usize], style: Style) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    let mut next = indices.iter().peekable();
+    for (i, c) in text.chars().enumerate() {
+        let matched = next.peek() == Some(&&i);
+        if matched {
+            next.next();
+        }
+        if matched != run_matched && !run.is_empty() {
+            let text = std::mem::take(&mut run);
+            spans.push(if run_matched { Span::styled(text, style) } else { Span::raw(text) });
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        spans.push(if run_matched { Span::styled(run, style) } else { Span::raw(run) });
+    }
+    Line::from(spans)
+}
+
+// byte counts as B / KB / MB / GB / TB with one decimal
+pub(crate) fn human_bytes(bytes: f64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+// the letter from /proc/<pid>/task/<tid>/stat
+fn thread_state(state: char) -> &'static str {
+    match state {
+        'R' => "running",
+        'S' => "sleeping",
+        'D' => "disk",
+        'Z' => "zombie",
+        'T' => "stopped",
+        't' => "traced",
+        'X' => "dead",
+        'I' => "idle",
+        _ => "unknown",
+    }
+}
+
+pub(crate) fn s_to_hms(secs: u64) -> (u64, u64, u64) {
     let h = secs / 3600;
     let m = (secs % 3600) / 60;
     let s = secs % 60;
@@ -0,0 +1,139 @@
+// named views: a filter, sort order and column set saved in ~/.xtop/xtoprc as
+//   view.db=sort=memory reverse=false columns=disk,core group=off search=substring cmdline=false filter=postgres
+// `filter=` comes last and takes the rest of the line, so it may contain spaces
+use ratatui::widgets::TableState;
+use crate::aggregate::Aggregate;
+use crate::app::{CgroupColumn, DiskColumns};
+use crate::search::SearchMode;
+
+const SORT_NAMES: [&str; 8] = ["pid", "name", "memory", "cpu", "read", "write", "recv", "sent"];
+
+#[derive(Clone)]
+pub struct SavedView {
+    pub name: String,
+    pub sort_col: u8,
+    pub reverse: bool,
+    pub disk_columns: DiskColumns,
+    pub net_columns: bool,
+    pub cgroup_column: CgroupColumn,
+    pub show_last_cpu: bool,
+    pub aggregate: Aggregate,
+    pub search_mode: SearchMode,
+    pub search_cmdline: bool,
+    pub filter: String,
+}
+
+impl SavedView {
+    // unknown settings are skipped, so views written by newer versions still load
+    pub fn parse(name: &str, value: &str) -> Self {
+        let mut view = SavedView {
+            name: name.to_string(),
+            sort_col: 2,
+            reverse: false,
+            disk_columns: DiskColumns::Off,
+            net_columns: false,
+            cgroup_column: CgroupColumn::Off,
+            show_last_cpu: false,
+            aggregate: Aggregate::Off,
+            search_mode: SearchMode::Substring,
+            search_cmdline: false,
+            filter: String::new(),
+        };
+        let (settings, filter) = match value.find("filter=") {
+            Some(i) => (&value[..i], value[i + "filter=".len()..].to_string()),
+            None => (value, String::new()),
+        };
+        view.filter = filter;
+        for (key, value) in settings.split_whitespace().filter_map(|s| s.split_once('=')) {
+            match key {
+                "sort" => {
+                    if let Some(i) = SORT_NAMES.iter().position(|s| *s == value) {
+                        view.sort_col = i as u8;
+                    }
+                }
+                "reverse" => view.reverse = value == "true",
+                "columns" => {
+                    for column in value.split(',') {
+                        match column {
+                            "disk" => view.disk_columns = DiskColumns::Rates,
+                            "disk-totals" => view.disk_columns = DiskColumns::Totals,
+                            "net" => view.net_columns = true,
+                            "cgroup" => view.cgroup_column = CgroupColumn::Shown,
+                            "cgroup-grouped" => view.cgroup_column = CgroupColumn::Grouped,
+                            "core" => view.show_last_cpu = true,
+                            _ => {}
+                        }
+                    }
+                }
+                "group" => {
+                    view.aggregate = match value {
+                        "name" => Aggregate::Name,
+                        "user" => Aggregate::User,
+                        "parent" => Aggregate::Parent,
+                        _ => Aggregate::Off,
+                    }
+                }
+                "search" => view.search_mode = SearchMode::from_name(value).unwrap_or(SearchMode::Substring),
+                "cmdline" => view.search_cmdline = value == "true",
+                _ => {}
+            }
+        }
+        view
+    }
+
+    pub fn to_value(&self) -> String {
+        let mut columns = Vec::new();
+        match self.disk_columns {
+            DiskColumns::Rates => columns.push("disk"),
+            DiskColumns::Totals => columns.push("disk-totals"),
+            DiskColumns::Off => {}
+        }
+        if self.net_columns {
+            columns.push("net");
+        }
+        match self.cgroup_column {
+            CgroupColumn::Shown => columns.push("cgroup"),
+            CgroupColumn::Grouped => columns.push("cgroup-grouped"),
+            CgroupColumn::Off => {}
+        }
+        if self.show_last_cpu {
+            columns.push("core");
+        }
+        format!(
+            "sort={} reverse={} columns={} group={} search={} cmdline={} filter={}",
+            SORT_NAMES.get(self.sort_col as usize).unwrap_or(&"memory"),
+            self.reverse,
+            columns.join(","),
+            self.aggregate.name(),
+            self.search_mode.name(),
+            self.search_cmdline,
+            self.filter,
+        )
+    }
+
+    // one-line summary for the picker
+    pub fn describe(&self) -> String {
+        let sort = SORT_NAMES.get(self.sort_col as usize).unwrap_or(&"memory");
+        let order = if self.reverse { " reversed" } else { "" };
+        if self.filter.is_empty() {
+            format!("by {}{}", sort, order)
+        } else {
+            format!("{} · by {}{}", self.filter, sort, order)
+        }
+    }
+}
+
+// state of the view picker popup
+pub struct ViewPicker {
+    pub table_state: TableState,
+    // the name being typed when saving the current view
+    pub naming: Option<String>,
+}
+
+impl Default for ViewPicker {
+    fn default() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select(Some(0));
+        Self { table_state, naming: None }
+    }
+}